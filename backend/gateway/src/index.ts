@@ -6,14 +6,33 @@ import { v4 as uuidv4 } from 'uuid';
 
 // The request now includes the type of analysis to perform.
 interface AnalysisJobRequest {
-    source_code: string;
+    source_code?: string;
     analysis_type: 'security' | 'portability' | 'awm' | 'staking' | 'gas' | 'upgrade' | 'ecosystem' | 'consensus'; 
     subnet_genesis?:any;// Enforce specific types
+    upgrade_config?: any; // The chain's upgrade.json (precompileUpgrades)
+    deployment_timestamp?: number;
+    target_network?: string; // Network name in the address registry, e.g. 'fuji'
+    target_profile?: any; // Deployment manifest and/or state snapshot of the target chain
+    native_token?: any; // Declared native token profile: { symbol, valueless }
+    evm_version?: string; // Compiler evmVersion, e.g. 'shanghai'
+    bytecode?: any; // Runtime bytecode hex, or a Foundry/Hardhat artifact JSON
+    targets?: any[]; // Named target chains to compare: [{ name, subnet_genesis, upgrade_config, ... }]
+    staking_rules?: any; // P-Chain staking rules of a Subnet, overriding the target_network's
 }
 
 interface AnalysisJob {
     job_id: string;
     source_code: string;
+    subnet_genesis?: any;
+    upgrade_config?: any;
+    deployment_timestamp?: number;
+    target_network?: string;
+    target_profile?: any;
+    native_token?: any;
+    evm_version?: string;
+    bytecode?: any;
+    targets?: any[];
+    staking_rules?: any;
 }
 
 const PORT = process.env.PORT || 8080;
@@ -45,8 +64,8 @@ async function main() {
                 const request: AnalysisJobRequest = JSON.parse(messageString);
 
                 // Basic validation
-                if (!request.source_code || !request.analysis_type) {
-                    ws.send(JSON.stringify({ error: '`source_code` and `analysis_type` fields are required.' }));
+                if ((!request.source_code && !request.bytecode) || !request.analysis_type) {
+                    ws.send(JSON.stringify({ error: '`analysis_type` and either `source_code` or `bytecode` are required.' }));
                     return;
                 }
 
@@ -56,8 +75,17 @@ async function main() {
 
                 const job: AnalysisJob = {
                     job_id: jobId,
-                    source_code: request.source_code,
-                    subnet_genesis: request.subnet_genesis
+                    source_code: request.source_code || '',
+                    subnet_genesis: request.subnet_genesis,
+                    upgrade_config: request.upgrade_config,
+                    deployment_timestamp: request.deployment_timestamp,
+                    target_network: request.target_network,
+                    target_profile: request.target_profile,
+                    native_token: request.native_token,
+                    evm_version: request.evm_version,
+                    bytecode: request.bytecode,
+                    targets: request.targets,
+                    staking_rules: request.staking_rules
                 };
                 
                 // --- DISPATCHER LOGIC ---
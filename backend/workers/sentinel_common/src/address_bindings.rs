@@ -0,0 +1,148 @@
+//! Names bound to address literals, and the member calls made on them.
+//!
+//! Contracts rarely call a precompile through a bare literal: the address usually sits in a
+//! constant, an immutable or a state variable set in the constructor, or in a local. These
+//! bindings let findings be attributed to the call sites instead of the declaration.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use regex::Regex;
+
+use crate::solidity::{line_at, matching_close, SourceOutline};
+
+pub struct AddressBindings {
+    // Contract-level names (constants, immutables, state variables) -> lowercase address.
+    globals: HashMap<String, String>,
+    // Names local to a function, keyed by the start of its body.
+    locals: HashMap<(usize, String), String>,
+    // Function bodies, for scoping locals.
+    bodies: Vec<Range<usize>>,
+    literal_regex: Regex,
+    identifier_regex: Regex,
+}
+
+/// A member call on an expression that resolves to an address, e.g. `STAKING.call{value: v}(data)`
+/// or `IStaking(PCHAIN).addDelegator(id, amount)`.
+#[derive(Debug, Clone)]
+pub struct AddressCall {
+    /// Lowercase address the receiver resolves to.
+    pub address: String,
+    pub line: u32,
+    /// The receiver, e.g. `IStaking(PCHAIN)`.
+    pub receiver: Range<usize>,
+    pub method: Range<usize>,
+    /// Inside of the call options braces, if any.
+    pub options: Option<Range<usize>>,
+    /// Inside of the argument parentheses.
+    pub arguments: Range<usize>,
+    /// Start of the enclosing function body.
+    pub scope: Option<usize>,
+}
+
+impl AddressBindings {
+    /// Collects bindings from masked source: state variable initializers and assignments anywhere
+    /// (which covers constructor-set immutables and locals), following one level of indirection.
+    pub fn collect(masked: &str, outline: &SourceOutline) -> Self {
+        let mut bindings = AddressBindings {
+            globals: HashMap::new(),
+            locals: HashMap::new(),
+            bodies: outline.functions.iter().map(|f| f.body.clone()).collect(),
+            literal_regex: Regex::new(r"\b0x[0-9a-fA-F]{40}\b").unwrap(),
+            identifier_regex: Regex::new(r"[A-Za-z_$][\w$]*").unwrap(),
+        };
+        let assignment = Regex::new(r"\b([A-Za-z_$][\w$]*)\s*=[^=>]").unwrap();
+        // The second pass picks up names bound to other names (`STAKING = IStaking(PCHAIN)`).
+        for _ in 0..2 {
+            for variable in &outline.state_variables {
+                if let Some(address) = variable.initializer.as_deref().and_then(|init| bindings.resolve(init, None)) {
+                    bindings.globals.insert(variable.name.clone(), address);
+                }
+            }
+            for c in assignment.captures_iter(masked) {
+                let whole = c.get(0).unwrap();
+                let value_end = masked[whole.end()..].find(';').map(|i| whole.end() + i).unwrap_or(masked.len());
+                let scope = bindings.scope_at(whole.start());
+                let Some(address) = bindings.resolve(&masked[whole.end() - 1..value_end], scope) else { continue };
+                let name = c[1].to_string();
+                if outline.state_variable(&name).is_some() {
+                    bindings.globals.insert(name, address);
+                } else if let Some(body) = scope {
+                    bindings.locals.insert((body, name), address);
+                }
+            }
+        }
+        bindings
+    }
+
+    /// Start of the body of the innermost function enclosing `offset`.
+    pub fn scope_at(&self, offset: usize) -> Option<usize> {
+        self.bodies.iter().filter(|b| b.contains(&offset)).min_by_key(|b| b.len()).map(|b| b.start)
+    }
+
+    /// The address an expression refers to: the first literal in it, or else the first bound name.
+    pub fn resolve(&self, expr: &str, scope: Option<usize>) -> Option<String> {
+        if let Some(m) = self.literal_regex.find(expr) {
+            return Some(m.as_str().to_lowercase());
+        }
+        self.identifier_regex.find_iter(expr).find_map(|m| {
+            scope.and_then(|body| self.locals.get(&(body, m.as_str().to_string()))).or_else(|| self.globals.get(m.as_str())).cloned()
+        })
+    }
+
+    /// Every member call whose receiver resolves to an address.
+    pub fn calls(&self, masked: &str) -> Vec<AddressCall> {
+        let member_call = Regex::new(r"\.\s*([A-Za-z_$][\w$]*)\s*[({]").unwrap();
+        let mut calls = Vec::new();
+        for c in member_call.captures_iter(masked) {
+            let dot = c.get(0).unwrap().start();
+            let mut open = c.get(0).unwrap().end() - 1;
+            let mut options = None;
+            if masked.as_bytes()[open] == b'{' {
+                let Some(close) = matching_close(masked, open) else { continue };
+                let rest = &masked[close + 1..];
+                if !rest.trim_start().starts_with('(') {
+                    continue;
+                }
+                options = Some(open + 1..close);
+                open = close + 1 + (rest.len() - rest.trim_start().len());
+            }
+            let Some(close) = matching_close(masked, open) else { continue };
+            let receiver = receiver_before(masked, dot);
+            let scope = self.scope_at(dot);
+            let Some(address) = self.resolve(&masked[receiver.clone()], scope) else { continue };
+            calls.push(AddressCall { address, line: line_at(masked, dot), receiver, method: c.get(1).unwrap().range(), options, arguments: open + 1..close, scope });
+        }
+        calls
+    }
+}
+
+/// The receiver of a member access ending at `dot`: an identifier or a call/cast such as `IStaking(x)`.
+fn receiver_before(masked: &str, dot: usize) -> Range<usize> {
+    let bytes = masked.as_bytes();
+    let mut end = dot;
+    while end > 0 && bytes[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    let mut start = end;
+    if start > 0 && bytes[start - 1] == b')' {
+        let mut depth = 0usize;
+        while start > 0 {
+            start -= 1;
+            match bytes[start] {
+                b')' => depth += 1,
+                b'(' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    while start > 0 && (bytes[start - 1].is_ascii_alphanumeric() || matches!(bytes[start - 1], b'_' | b'$')) {
+        start -= 1;
+    }
+    start..end
+}
@@ -0,0 +1,182 @@
+//! Extraction and classification of 20-byte address literals in Solidity source.
+
+use crate::addresses::AddressRegistry;
+use crate::precompiles::{Precompile, PrecompileRegistry};
+use crate::solidity::mask_comments_and_strings;
+
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+const DEAD_ADDRESSES: &[&str] = &[
+    "0x000000000000000000000000000000000000dead",
+    "0xdead000000000000000000000000000000000000",
+];
+// Address prefixes reserved for Avalanche and Subnet-EVM stateful precompiles.
+const PRECOMPILE_RANGES: &[&str] = &["0x01000000000000000000000000000000000000", "0x02000000000000000000000000000000000000", "0x03000000000000000000000000000000000000"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressOccurrence {
+    pub line: u32,
+    // The trimmed source line the literal appears on.
+    pub context: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AddressLiteral {
+    /// Lowercase form, used for comparisons.
+    pub address: String,
+    /// The first occurrence written with a bad EIP-55 checksum, as (line, literal).
+    pub invalid_checksum: Option<(u32, String)>,
+    pub occurrences: Vec<AddressOccurrence>,
+}
+
+impl AddressLiteral {
+    pub fn first_line(&self) -> u32 {
+        self.occurrences.first().map(|o| o.line).unwrap_or(0)
+    }
+
+    /// Lines of every occurrence after the first.
+    pub fn other_lines(&self) -> Vec<u32> {
+        self.occurrences.iter().skip(1).map(|o| o.line).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum AddressClass<'a> {
+    Zero,
+    Dead,
+    Precompile(&'a Precompile),
+    /// Inside a reserved precompile range but not in the registry.
+    UnregisteredPrecompile,
+    KnownProtocol,
+    /// Not a precompile, burn address or registered protocol: an EOA or an unknown contract.
+    EoaLike,
+}
+
+/// Finds every 40-hex-digit literal outside comments and strings, grouped by address in order
+/// of first appearance. Hex runs of any other length are not addresses and are ignored.
+pub fn extract_address_literals(source: &str) -> Vec<AddressLiteral> {
+    let masked = mask_comments_and_strings(source);
+    let original_lines: Vec<&str> = source.lines().collect();
+    let mut literals: Vec<AddressLiteral> = Vec::new();
+
+    for (i, line) in masked.lines().enumerate() {
+        let bytes = line.as_bytes();
+        let mut pos = 0;
+        while let Some(offset) = line[pos..].find("0x") {
+            let start = pos + offset;
+            let digits_start = start + 2;
+            let digits_end = digits_start + bytes[digits_start..].iter().take_while(|b| b.is_ascii_hexdigit()).count();
+            let preceded_by_word = start > 0 && is_word_byte(bytes[start - 1]);
+            let followed_by_word = bytes.get(digits_end).is_some_and(|b| is_word_byte(*b));
+            pos = digits_end.max(digits_start);
+
+            if preceded_by_word || followed_by_word || digits_end - digits_start != 40 {
+                continue;
+            }
+            let literal = &line[start..digits_end];
+            let occurrence = AddressOccurrence {
+                line: (i + 1) as u32,
+                context: original_lines.get(i).map(|l| l.trim().to_string()).unwrap_or_default(),
+            };
+            let invalid_checksum = (!is_valid_checksum(literal)).then(|| (occurrence.line, literal.to_string()));
+            let address = literal.to_lowercase();
+            match literals.iter_mut().find(|l| l.address == address) {
+                Some(existing) => {
+                    if !existing.occurrences.contains(&occurrence) {
+                        existing.occurrences.push(occurrence);
+                    }
+                    if existing.invalid_checksum.is_none() {
+                        existing.invalid_checksum = invalid_checksum;
+                    }
+                }
+                None => literals.push(AddressLiteral { address, invalid_checksum, occurrences: vec![occurrence] }),
+            }
+        }
+    }
+    literals
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$'
+}
+
+/// EIP-55 mixed-case checksum encoding of a 20-byte hex address.
+pub fn to_checksum_address(address: &str) -> String {
+    let lower = address.trim_start_matches("0x").trim_start_matches("0X").to_lowercase();
+    let hash = crate::keccak256(lower.as_bytes());
+    let mut out = String::from("0x");
+    for (i, c) in lower.chars().enumerate() {
+        let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+        out.push(if c.is_ascii_alphabetic() && nibble >= 8 { c.to_ascii_uppercase() } else { c });
+    }
+    out
+}
+
+/// solc only accepts 40-digit hex literals as addresses when they match their EIP-55 checksum.
+/// Literals without letters are trivially valid.
+pub fn is_valid_checksum(literal: &str) -> bool {
+    let digits = literal.trim_start_matches("0x");
+    !digits.chars().any(|c| c.is_ascii_alphabetic()) || to_checksum_address(literal)[2..] == *digits
+}
+
+pub fn classify<'a>(address: &str, precompiles: &'a PrecompileRegistry, addresses: &AddressRegistry) -> AddressClass<'a> {
+    let address = address.to_lowercase();
+    if address == ZERO_ADDRESS {
+        AddressClass::Zero
+    } else if DEAD_ADDRESSES.contains(&address.as_str()) {
+        AddressClass::Dead
+    } else if let Some(precompile) = precompiles.by_address(&address) {
+        AddressClass::Precompile(precompile)
+    } else if PRECOMPILE_RANGES.iter().any(|prefix| address.starts_with(prefix)) {
+        AddressClass::UnregisteredPrecompile
+    } else if addresses.lookup(&address).next().is_some() {
+        AddressClass::KnownProtocol
+    } else {
+        AddressClass::EoaLike
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vectors from EIP-55.
+    const CHECKSUMMED: &[&str] = &[
+        "0x52908400098527886E0F7030069857D2E4169EE7",
+        "0x8617E340B3D01FA5F11F306F4090FD50E238070D",
+        "0xde709f2102306220921060314715629080e2fb77",
+        "0x27b1fdb04752bbc536007a920d24acb045561c26",
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+
+    #[test]
+    fn checksums_match_eip55_vectors() {
+        for &address in CHECKSUMMED {
+            assert_eq!(to_checksum_address(&address.to_lowercase()), address);
+            assert_eq!(to_checksum_address(&address.to_uppercase().replacen("0X", "0x", 1)), address);
+            assert!(is_valid_checksum(address), "{}", address);
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_case() {
+        assert!(!is_valid_checksum("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"));
+        assert!(!is_valid_checksum("0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+    }
+
+    #[test]
+    fn digit_only_literals_are_valid() {
+        assert!(is_valid_checksum("0x0100000000000000000000000000000000000000"));
+    }
+
+    #[test]
+    fn extracts_only_twenty_byte_literals_outside_comments() {
+        let source = "// 0x52908400098527886E0F7030069857D2E4169EE7\naddress a = 0x8617E340B3D01FA5F11F306F4090FD50E238070D;\nbytes32 h = 0x8617e340b3d01fa5f11f306f4090fd50e238070d8617e340b3d01fa5f11f306f;\n";
+        let literals = extract_address_literals(source);
+        assert_eq!(literals.len(), 1);
+        assert_eq!(literals[0].first_line(), 2);
+        assert!(literals[0].invalid_checksum.is_none());
+    }
+}
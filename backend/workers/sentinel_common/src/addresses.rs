@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// One JSON or TOML file per network (C-Chain mainnet, Fuji, named Subnets). The directory is
+// re-read whenever a file in it changes, so new protocols can be added without a rebuild.
+pub const REGISTRY_DIR_ENV_VAR: &str = "SENTINEL_ADDRESS_REGISTRY_DIR";
+const DEFAULT_REGISTRY_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/registry/addresses");
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DeploymentStatus {
+    Active,
+    Deprecated,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KnownAddress {
+    pub address: String,
+    pub protocol: String,
+    pub category: String,
+    pub status: DeploymentStatus,
+    // keccak256 of the runtime code on this network, used to detect different code elsewhere.
+    #[serde(default)]
+    pub code_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkAddressBook {
+    pub network: String,
+    pub chain_id: Option<u64>,
+    #[serde(default)]
+    pub addresses: Vec<KnownAddress>,
+}
+
+#[derive(Debug, Default)]
+pub struct AddressRegistry {
+    dir: PathBuf,
+    fingerprint: Vec<(PathBuf, SystemTime)>,
+    networks: Vec<NetworkAddressBook>,
+}
+
+impl AddressRegistry {
+    /// Loads the directory named by `SENTINEL_ADDRESS_REGISTRY_DIR`, or the bundled registry.
+    pub fn load() -> Result<Self, String> {
+        Self::load_dir(Self::configured_dir())
+    }
+
+    /// The directory named by `SENTINEL_ADDRESS_REGISTRY_DIR`, or the bundled registry's.
+    pub fn configured_dir() -> PathBuf {
+        env::var(REGISTRY_DIR_ENV_VAR).map(PathBuf::from).unwrap_or_else(|_| PathBuf::from(DEFAULT_REGISTRY_DIR))
+    }
+
+    /// An empty registry watching `dir`, picked up by `reload_if_changed` once it holds valid files.
+    pub fn empty_at(dir: impl Into<PathBuf>) -> Self {
+        AddressRegistry { dir: dir.into(), ..Default::default() }
+    }
+
+    pub fn load_dir(dir: impl Into<PathBuf>) -> Result<Self, String> {
+        let mut registry = Self::empty_at(dir);
+        registry.reload()?;
+        Ok(registry)
+    }
+
+    /// Re-reads the registry directory if any file was added, removed or modified.
+    /// Returns `Ok(true)` when the registry was reloaded.
+    pub fn reload_if_changed(&mut self) -> Result<bool, String> {
+        if registry_files(&self.dir)? == self.fingerprint {
+            return Ok(false);
+        }
+        self.reload()?;
+        Ok(true)
+    }
+
+    fn reload(&mut self) -> Result<(), String> {
+        let files = registry_files(&self.dir)?;
+        let mut networks = Vec::new();
+        for (path, _) in &files {
+            networks.push(parse_address_book(path)?);
+        }
+        self.networks = networks;
+        self.fingerprint = files;
+        Ok(())
+    }
+
+    pub fn networks(&self) -> impl Iterator<Item = &NetworkAddressBook> {
+        self.networks.iter()
+    }
+
+    /// Every network whose address book contains `address`, with the matching entry.
+    pub fn lookup<'a>(&'a self, address: &'a str) -> impl Iterator<Item = (&'a NetworkAddressBook, &'a KnownAddress)> + 'a {
+        self.networks.iter().flat_map(move |book| {
+            book.addresses.iter().filter(move |a| a.address.eq_ignore_ascii_case(address)).map(move |a| (book, a))
+        })
+    }
+}
+
+fn registry_files(dir: &Path) -> Result<Vec<(PathBuf, SystemTime)>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read address registry '{}': {}", dir.display(), e))?;
+    let mut files: Vec<(PathBuf, SystemTime)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| matches!(path.extension().and_then(|e| e.to_str()), Some("json") | Some("toml")))
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH);
+            (path, modified)
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn parse_address_book(path: &Path) -> Result<NetworkAddressBook, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let parsed = if path.extension().and_then(|e| e.to_str()) == Some("toml") {
+        toml::from_str(&contents).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_str(&contents).map_err(|e| e.to_string())
+    };
+    parsed.map_err(|e| format!("Invalid address registry '{}': {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_registry_picks_up_a_later_valid_file() {
+        let dir = env::temp_dir().join(format!("sentinel-address-registry-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut registry = AddressRegistry::empty_at(&dir);
+        assert!(registry.reload_if_changed().is_err());
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("fuji.json"), r#"{ "network": "fuji", "addresses": [{ "address": "0x00000000000000000000000000000000000000aa", "protocol": "Test", "category": "token", "status": "active" }] }"#).unwrap();
+        assert_eq!(registry.reload_if_changed(), Ok(true));
+        assert_eq!(registry.lookup("0x00000000000000000000000000000000000000AA").count(), 1);
+        assert_eq!(registry.reload_if_changed(), Ok(false));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
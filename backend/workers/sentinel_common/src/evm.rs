@@ -0,0 +1,327 @@
+//! EVM hard-fork levels, the opcode table and a bytecode disassembler.
+
+use serde::{Deserialize, Serialize};
+
+/// Hard forks that introduced opcodes, in activation order. Forks that only changed pricing
+/// or consensus rules are folded into the preceding level (Petersburg into Constantinople,
+/// Paris into London, Prague into Cancun).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum EvmFork {
+    Frontier,
+    Homestead,
+    Byzantium,
+    Constantinople,
+    Istanbul,
+    Berlin,
+    London,
+    Shanghai,
+    Cancun,
+}
+
+impl EvmFork {
+    /// Parses solc `evmVersion` names.
+    pub fn parse(name: &str) -> Option<Self> {
+        Some(match name.trim().to_lowercase().as_str() {
+            "frontier" => EvmFork::Frontier,
+            "homestead" | "tangerinewhistle" | "spuriousdragon" => EvmFork::Homestead,
+            "byzantium" => EvmFork::Byzantium,
+            "constantinople" | "petersburg" => EvmFork::Constantinople,
+            "istanbul" => EvmFork::Istanbul,
+            "berlin" => EvmFork::Berlin,
+            "london" | "paris" => EvmFork::London,
+            "shanghai" => EvmFork::Shanghai,
+            "cancun" | "prague" | "osaka" => EvmFork::Cancun,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EvmFork::Frontier => "frontier",
+            EvmFork::Homestead => "homestead",
+            EvmFork::Byzantium => "byzantium",
+            EvmFork::Constantinople => "constantinople",
+            EvmFork::Istanbul => "istanbul",
+            EvmFork::Berlin => "berlin",
+            EvmFork::London => "london",
+            EvmFork::Shanghai => "shanghai",
+            EvmFork::Cancun => "cancun",
+        }
+    }
+
+    /// The `evmVersion` solc targets by default.
+    pub fn solc_default(version: (u32, u32, u32)) -> Self {
+        match version {
+            v if v < (0, 4, 21) => EvmFork::Homestead,
+            v if v < (0, 5, 5) => EvmFork::Byzantium,
+            v if v < (0, 5, 14) => EvmFork::Constantinople,
+            v if v < (0, 8, 5) => EvmFork::Istanbul,
+            v if v < (0, 8, 7) => EvmFork::Berlin,
+            v if v < (0, 8, 20) => EvmFork::London,
+            v if v < (0, 8, 25) => EvmFork::Shanghai,
+            _ => EvmFork::Cancun,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opcode {
+    pub byte: u8,
+    /// Mnemonic without the numeric suffix of `PUSHn`/`DUPn`/`SWAPn`/`LOGn`.
+    pub mnemonic: &'static str,
+    pub since: EvmFork,
+}
+
+impl Opcode {
+    pub fn name(&self) -> String {
+        match self.mnemonic {
+            "PUSH" => format!("PUSH{}", self.byte - 0x5f),
+            "DUP" => format!("DUP{}", self.byte - 0x7f),
+            "SWAP" => format!("SWAP{}", self.byte - 0x8f),
+            "LOG" => format!("LOG{}", self.byte - 0xa0),
+            m => m.to_string(),
+        }
+    }
+
+    /// Number of immediate bytes following the opcode.
+    pub fn immediate_size(&self) -> usize {
+        if (0x60..=0x7f).contains(&self.byte) { (self.byte - 0x5f) as usize } else { 0 }
+    }
+}
+
+pub fn opcode(byte: u8) -> Option<Opcode> {
+    use EvmFork::*;
+    let (mnemonic, since) = match byte {
+        0x00 => ("STOP", Frontier),
+        0x01 => ("ADD", Frontier),
+        0x02 => ("MUL", Frontier),
+        0x03 => ("SUB", Frontier),
+        0x04 => ("DIV", Frontier),
+        0x05 => ("SDIV", Frontier),
+        0x06 => ("MOD", Frontier),
+        0x07 => ("SMOD", Frontier),
+        0x08 => ("ADDMOD", Frontier),
+        0x09 => ("MULMOD", Frontier),
+        0x0a => ("EXP", Frontier),
+        0x0b => ("SIGNEXTEND", Frontier),
+        0x10 => ("LT", Frontier),
+        0x11 => ("GT", Frontier),
+        0x12 => ("SLT", Frontier),
+        0x13 => ("SGT", Frontier),
+        0x14 => ("EQ", Frontier),
+        0x15 => ("ISZERO", Frontier),
+        0x16 => ("AND", Frontier),
+        0x17 => ("OR", Frontier),
+        0x18 => ("XOR", Frontier),
+        0x19 => ("NOT", Frontier),
+        0x1a => ("BYTE", Frontier),
+        0x1b => ("SHL", Constantinople),
+        0x1c => ("SHR", Constantinople),
+        0x1d => ("SAR", Constantinople),
+        0x20 => ("KECCAK256", Frontier),
+        0x30 => ("ADDRESS", Frontier),
+        0x31 => ("BALANCE", Frontier),
+        0x32 => ("ORIGIN", Frontier),
+        0x33 => ("CALLER", Frontier),
+        0x34 => ("CALLVALUE", Frontier),
+        0x35 => ("CALLDATALOAD", Frontier),
+        0x36 => ("CALLDATASIZE", Frontier),
+        0x37 => ("CALLDATACOPY", Frontier),
+        0x38 => ("CODESIZE", Frontier),
+        0x39 => ("CODECOPY", Frontier),
+        0x3a => ("GASPRICE", Frontier),
+        0x3b => ("EXTCODESIZE", Frontier),
+        0x3c => ("EXTCODECOPY", Frontier),
+        0x3d => ("RETURNDATASIZE", Byzantium),
+        0x3e => ("RETURNDATACOPY", Byzantium),
+        0x3f => ("EXTCODEHASH", Constantinople),
+        0x40 => ("BLOCKHASH", Frontier),
+        0x41 => ("COINBASE", Frontier),
+        0x42 => ("TIMESTAMP", Frontier),
+        0x43 => ("NUMBER", Frontier),
+        0x44 => ("PREVRANDAO", Frontier),
+        0x45 => ("GASLIMIT", Frontier),
+        0x46 => ("CHAINID", Istanbul),
+        0x47 => ("SELFBALANCE", Istanbul),
+        0x48 => ("BASEFEE", London),
+        0x49 => ("BLOBHASH", Cancun),
+        0x4a => ("BLOBBASEFEE", Cancun),
+        0x50 => ("POP", Frontier),
+        0x51 => ("MLOAD", Frontier),
+        0x52 => ("MSTORE", Frontier),
+        0x53 => ("MSTORE8", Frontier),
+        0x54 => ("SLOAD", Frontier),
+        0x55 => ("SSTORE", Frontier),
+        0x56 => ("JUMP", Frontier),
+        0x57 => ("JUMPI", Frontier),
+        0x58 => ("PC", Frontier),
+        0x59 => ("MSIZE", Frontier),
+        0x5a => ("GAS", Frontier),
+        0x5b => ("JUMPDEST", Frontier),
+        0x5c => ("TLOAD", Cancun),
+        0x5d => ("TSTORE", Cancun),
+        0x5e => ("MCOPY", Cancun),
+        0x5f => ("PUSH0", Shanghai),
+        0x60..=0x7f => ("PUSH", Frontier),
+        0x80..=0x8f => ("DUP", Frontier),
+        0x90..=0x9f => ("SWAP", Frontier),
+        0xa0..=0xa4 => ("LOG", Frontier),
+        0xf0 => ("CREATE", Frontier),
+        0xf1 => ("CALL", Frontier),
+        0xf2 => ("CALLCODE", Frontier),
+        0xf3 => ("RETURN", Frontier),
+        0xf4 => ("DELEGATECALL", Homestead),
+        0xf5 => ("CREATE2", Constantinople),
+        0xfa => ("STATICCALL", Byzantium),
+        0xfd => ("REVERT", Byzantium),
+        0xfe => ("INVALID", Frontier),
+        0xff => ("SELFDESTRUCT", Frontier),
+        _ => return None,
+    };
+    Some(Opcode { byte, mnemonic, since })
+}
+
+/// Looks up an opcode by mnemonic, case-insensitively; Yul builtins share the opcode names.
+pub fn opcode_by_name(name: &str) -> Option<Opcode> {
+    let name = name.to_uppercase();
+    (0..=u8::MAX).filter_map(opcode).find(|op| op.name() == name || (name == "SHA3" && op.mnemonic == "KECCAK256"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub offset: usize,
+    pub byte: u8,
+    /// `None` for bytes that are not assigned an opcode (they execute as INVALID).
+    pub opcode: Option<Opcode>,
+    pub immediate: Vec<u8>,
+}
+
+impl Instruction {
+    pub fn is(&self, name: &str) -> bool {
+        self.opcode.is_some_and(|op| op.mnemonic == name || op.name() == name)
+    }
+
+    /// The pushed value for PUSH instructions, as a big-endian byte string (empty for PUSH0).
+    pub fn push_value(&self) -> Option<&[u8]> {
+        self.opcode.filter(|op| op.mnemonic == "PUSH" || op.mnemonic == "PUSH0").map(|_| self.immediate.as_slice())
+    }
+}
+
+/// Linear-sweep disassembly. A truncated trailing PUSH keeps the bytes that are present.
+pub fn disassemble(code: &[u8]) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut offset = 0;
+    while offset < code.len() {
+        let byte = code[offset];
+        let op = opcode(byte);
+        let size = op.map(|o| o.immediate_size()).unwrap_or(0);
+        let end = (offset + 1 + size).min(code.len());
+        instructions.push(Instruction { offset, byte, opcode: op, immediate: code[offset + 1..end].to_vec() });
+        offset += 1 + size;
+    }
+    instructions
+}
+
+/// Drops the CBOR metadata solc appends to runtime code, whose bytes would otherwise be
+/// disassembled as instructions. The last two bytes hold the metadata length.
+pub fn strip_metadata(code: &[u8]) -> &[u8] {
+    if code.len() < 2 {
+        return code;
+    }
+    let len = u16::from_be_bytes([code[code.len() - 2], code[code.len() - 1]]) as usize;
+    let start = code.len().checked_sub(len + 2);
+    match start {
+        // CBOR maps with 1-5 entries start with 0xa1..0xa5.
+        Some(start) if (0xa1..=0xa5).contains(&code[start]) => &code[..start],
+        _ => code,
+    }
+}
+
+/// One entry of a solc source map (`s:l:f:j:m`), with the compression resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceMapEntry {
+    pub start: i64,
+    pub length: i64,
+    /// Index of the source file; -1 for compiler-generated code.
+    pub file: i64,
+}
+
+/// Parses a compressed solc source map into one entry per instruction.
+pub fn parse_source_map(source_map: &str) -> Vec<SourceMapEntry> {
+    let mut current = SourceMapEntry { start: -1, length: -1, file: -1 };
+    source_map
+        .split(';')
+        .map(|entry| {
+            let mut fields = entry.split(':');
+            for slot in [&mut current.start, &mut current.length, &mut current.file] {
+                if let Some(value) = fields.next().filter(|f| !f.is_empty()).and_then(|f| f.parse().ok()) {
+                    *slot = value;
+                }
+            }
+            current
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_immediates_are_not_decoded_as_instructions() {
+        // PUSH2 0x5b01, ADD, PUSH0, JUMPDEST
+        let instructions = disassemble(&[0x61, 0x5b, 0x01, 0x01, 0x5f, 0x5b]);
+        let offsets: Vec<usize> = instructions.iter().map(|i| i.offset).collect();
+        assert_eq!(offsets, vec![0, 3, 4, 5]);
+        assert_eq!(instructions[0].push_value(), Some(&[0x5b, 0x01][..]));
+        assert!(instructions[1].is("ADD"));
+        assert_eq!(instructions[2].push_value(), Some(&[][..]));
+        assert!(instructions[3].is("JUMPDEST"));
+    }
+
+    #[test]
+    fn truncated_push_keeps_the_bytes_present() {
+        let instructions = disassemble(&[0x00, 0x63, 0xaa, 0xbb]);
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[1].immediate, vec![0xaa, 0xbb]);
+        assert!(disassemble(&[0x7f])[0].immediate.is_empty());
+    }
+
+    #[test]
+    fn unassigned_bytes_have_no_opcode() {
+        let instructions = disassemble(&[0x0c]);
+        assert_eq!(instructions.len(), 1);
+        assert!(instructions[0].opcode.is_none());
+    }
+
+    #[test]
+    fn strips_cbor_metadata() {
+        let code = [0x60, 0x01, 0x00];
+        // A one-entry CBOR map, then its length (5) as two big-endian bytes.
+        let with_metadata: Vec<u8> = code.iter().copied().chain([0xa1, 0x64, 0x01, 0x02, 0x03, 0x00, 0x05]).collect();
+        assert_eq!(strip_metadata(&with_metadata), &code);
+    }
+
+    #[test]
+    fn keeps_code_without_metadata() {
+        let code = [0x60, 0x01, 0x60, 0x02, 0x00, 0x03];
+        assert_eq!(strip_metadata(&code), &code);
+        // A length longer than the code.
+        assert_eq!(strip_metadata(&[0x00, 0xff]), &[0x00, 0xff]);
+        assert_eq!(strip_metadata(&[0xa1]), &[0xa1]);
+    }
+
+    #[test]
+    fn source_map_entries_inherit_omitted_fields() {
+        let entries = parse_source_map("1:2:0:-;:3;5::1;;0:10:-1");
+        let fields: Vec<(i64, i64, i64)> = entries.iter().map(|e| (e.start, e.length, e.file)).collect();
+        assert_eq!(fields, vec![(1, 2, 0), (1, 3, 0), (5, 3, 1), (5, 3, 1), (0, 10, -1)]);
+    }
+
+    #[test]
+    fn opcode_lookup_by_name() {
+        assert_eq!(opcode_by_name("sha3").map(|o| o.byte), Some(0x20));
+        assert_eq!(opcode_by_name("push32").map(|o| o.immediate_size()), Some(32));
+    }
+}
@@ -0,0 +1,51 @@
+//! Gas costs of the operations a fixed gas amount is usually meant to cover.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// Post-Berlin (EIP-2929) pricing, which Subnet-EVM and the C-Chain follow unless a Subnet says otherwise.
+const BUILTIN_SCHEDULE: &str = include_str!("../registry/gas_schedule.json");
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GasSchedule {
+    /// Gas forwarded by `.transfer` and `.send`, and added to value-bearing calls.
+    pub call_stipend: u64,
+    pub cold_sload: u64,
+    pub warm_sload: u64,
+    pub sstore_set: u64,
+    /// SSTORE reverts when no more than this much gas is left (EIP-2200).
+    pub sstore_sentry: u64,
+    pub cold_account_access: u64,
+    pub call_value_transfer: u64,
+    pub log_base: u64,
+    pub log_topic: u64,
+}
+
+impl GasSchedule {
+    pub fn builtin() -> Self {
+        serde_json::from_str(BUILTIN_SCHEDULE).expect("built-in gas schedule is valid")
+    }
+
+    /// The built-in schedule with the given entries (same camelCase keys) replaced.
+    pub fn with_overrides(overrides: &Map<String, Value>) -> Result<Self, String> {
+        let mut schedule: Map<String, Value> = serde_json::from_str(BUILTIN_SCHEDULE).expect("built-in gas schedule is valid");
+        schedule.extend(overrides.iter().map(|(k, v)| (k.clone(), v.clone())));
+        serde_json::from_value(Value::Object(schedule)).map_err(|e| format!("invalid gas schedule: {}", e))
+    }
+
+    /// Common callee operations that cost more than `gas`, cheapest first.
+    pub fn unaffordable(&self, gas: u64) -> Vec<(&'static str, u64)> {
+        let mut operations = vec![
+            ("emitting an event with one topic", self.log_base + self.log_topic),
+            ("a cold storage read", self.cold_sload),
+            ("a call to another contract", self.cold_account_access),
+            // SSTORE needs more than the sentry left over, and a fresh slot costs the full set price.
+            ("a storage write", (self.sstore_sentry + 1).max(self.cold_sload + self.sstore_set)),
+            ("forwarding value in a nested call", self.call_value_transfer),
+        ];
+        operations.retain(|(_, cost)| *cost > gas);
+        operations.sort_by_key(|(_, cost)| *cost);
+        operations
+    }
+}
@@ -0,0 +1,51 @@
+//! Shared registries and helpers for the Rust Sentinel workers.
+
+pub mod address_bindings;
+pub mod address_literals;
+pub mod addresses;
+pub mod evm;
+pub mod gas;
+pub mod precompiles;
+pub mod solidity;
+pub mod staking_rules;
+
+use tiny_keccak::{Hasher, Keccak};
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    let mut output = [0u8; 32];
+    hasher.update(data);
+    hasher.finalize(&mut output);
+    output
+}
+
+/// Lowercase `0x`-prefixed hex encoding.
+pub fn encode_hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(2 + bytes.len() * 2);
+    out.push_str("0x");
+    for b in bytes {
+        out.push_str(&format!("{:02x}", b));
+    }
+    out
+}
+
+/// Decodes a hex string with or without a `0x` prefix.
+pub fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim();
+    let s = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s);
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keccak256_matches_known_digests() {
+        assert_eq!(encode_hex(&keccak256(b"")), "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+        assert_eq!(encode_hex(&keccak256(b"transfer(address,uint256)")[..4]), "0xa9059cbb");
+    }
+}
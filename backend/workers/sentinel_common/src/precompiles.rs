@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+
+// Built-in catalogue. Subnets with custom stateful precompiles can register their own
+// entries by pointing `SENTINEL_PRECOMPILE_REGISTRY` at a file with the same format.
+const BUILTIN_REGISTRY: &str = include_str!("../registry/precompiles.json");
+pub const REGISTRY_ENV_VAR: &str = "SENTINEL_PRECOMPILE_REGISTRY";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PrecompileKind {
+    /// Standard Ethereum precompiles, available on every EVM chain.
+    Ethereum,
+    /// Avalanche-specific precompiles that are not configured through the genesis.
+    Avalanche,
+    /// Subnet-EVM stateful precompiles, enabled through a genesis/upgrade config key.
+    SubnetEvm,
+    /// Precompiles registered by a Subnet operator.
+    Custom,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum AllowListRole {
+    Enabled,
+    Manager,
+    Admin,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PrecompileFunction {
+    pub signature: String,
+    #[serde(default)]
+    pub required_role: Option<AllowListRole>,
+    #[serde(default)]
+    pub payable: bool,
+    /// What each argument is, e.g. `stakeDuration`, for checking values against network rules.
+    #[serde(default)]
+    pub parameters: Vec<String>,
+    /// What the value sent with the call is, e.g. `validatorStake`.
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+impl PrecompileFunction {
+    /// The 4-byte function selector as a lowercase `0x`-prefixed hex string.
+    pub fn selector(&self) -> String {
+        let hash = crate::keccak256(self.signature.as_bytes());
+        crate::encode_hex(&hash[..4])
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Precompile {
+    pub address: String,
+    pub name: String,
+    pub kind: PrecompileKind,
+    #[serde(default)]
+    pub config_key: Option<String>,
+    #[serde(default)]
+    pub staking: bool,
+    #[serde(default)]
+    pub functions: Vec<PrecompileFunction>,
+}
+
+impl Precompile {
+    pub fn is_always_enabled(&self) -> bool {
+        self.kind == PrecompileKind::Ethereum
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PrecompileRegistry {
+    precompiles: Vec<Precompile>,
+}
+
+impl PrecompileRegistry {
+    pub fn builtin() -> Self {
+        serde_json::from_str(BUILTIN_REGISTRY).expect("built-in precompile registry is valid JSON")
+    }
+
+    /// The built-in catalogue plus any file named by `SENTINEL_PRECOMPILE_REGISTRY`.
+    pub fn load() -> Result<Self, String> {
+        let mut registry = Self::builtin();
+        if let Ok(path) = env::var(REGISTRY_ENV_VAR) {
+            registry.extend_from_file(&path)?;
+        }
+        Ok(registry)
+    }
+
+    /// Adds the entries of a registry file, replacing built-in entries with the same address.
+    pub fn extend_from_file(&mut self, path: &str) -> Result<(), String> {
+        let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read precompile registry '{}': {}", path, e))?;
+        let extra: PrecompileRegistry = serde_json::from_str(&contents).map_err(|e| format!("Invalid precompile registry '{}': {}", path, e))?;
+        for precompile in extra.precompiles {
+            self.precompiles.retain(|p| !p.address.eq_ignore_ascii_case(&precompile.address));
+            self.precompiles.push(precompile);
+        }
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Precompile> {
+        self.precompiles.iter()
+    }
+
+    pub fn by_address(&self, address: &str) -> Option<&Precompile> {
+        self.precompiles.iter().find(|p| p.address.eq_ignore_ascii_case(address))
+    }
+
+    pub fn staking(&self) -> impl Iterator<Item = &Precompile> {
+        self.precompiles.iter().filter(|p| p.staking)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selector_is_first_four_bytes_of_signature_hash() {
+        let function = PrecompileFunction { signature: "transfer(address,uint256)".to_string(), required_role: None, payable: false, parameters: Vec::new(), value: None };
+        assert_eq!(function.selector(), "0xa9059cbb");
+    }
+
+    #[test]
+    fn builtin_registry_lists_staking_precompile() {
+        let registry = PrecompileRegistry::builtin();
+        let staking: Vec<&Precompile> = registry.staking().collect();
+        assert_eq!(staking.len(), 1);
+        assert!(staking[0].functions.iter().any(|f| f.signature == "addDelegator(string,uint64)" && f.payable));
+    }
+}
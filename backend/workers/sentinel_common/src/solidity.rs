@@ -0,0 +1,505 @@
+//! Lightweight helpers for scanning Solidity source text.
+
+use std::ops::Range;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// Blanks out comments and the contents of string literals, keeping line breaks and byte offsets
+/// intact so positions in the masked text map straight back to the original source.
+pub fn mask_comments_and_strings(source: &str) -> String {
+    #[derive(PartialEq)]
+    enum State {
+        Code,
+        LineComment,
+        BlockComment,
+        Str(u8),
+    }
+
+    let bytes = source.as_bytes();
+    let mut out = bytes.to_vec();
+    let mut state = State::Code;
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let next = bytes.get(i + 1).copied();
+        match state {
+            State::Code => match (c, next) {
+                (b'/', Some(b'/')) => {
+                    state = State::LineComment;
+                    out[i] = b' ';
+                    out[i + 1] = b' ';
+                    i += 1;
+                }
+                (b'/', Some(b'*')) => {
+                    state = State::BlockComment;
+                    out[i] = b' ';
+                    out[i + 1] = b' ';
+                    i += 1;
+                }
+                (b'"', _) | (b'\'', _) => state = State::Str(c),
+                _ => {}
+            },
+            State::LineComment => {
+                if c == b'\n' {
+                    state = State::Code;
+                } else {
+                    out[i] = b' ';
+                }
+            }
+            State::BlockComment => {
+                if c == b'*' && next == Some(b'/') {
+                    state = State::Code;
+                    out[i] = b' ';
+                    out[i + 1] = b' ';
+                    i += 1;
+                } else if c != b'\n' {
+                    out[i] = b' ';
+                }
+            }
+            State::Str(quote) => {
+                if c == b'\\' && next.is_some() {
+                    out[i] = b' ';
+                    if next != Some(b'\n') {
+                        out[i + 1] = b' ';
+                    }
+                    i += 1;
+                } else if c == quote {
+                    state = State::Code;
+                } else if c != b'\n' {
+                    out[i] = b' ';
+                }
+            }
+        }
+        i += 1;
+    }
+    // Every byte of a multi-byte character is blanked together, so the result stays valid UTF-8.
+    String::from_utf8(out).expect("masking only replaces whole characters")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FunctionKind {
+    Function,
+    Constructor,
+    Modifier,
+    Fallback,
+    Receive,
+}
+
+/// A function, constructor or modifier with a body.
+#[derive(Debug, Clone)]
+pub struct FunctionSpan {
+    /// Declared name; the keyword itself for constructors, fallback and receive.
+    pub name: String,
+    pub kind: FunctionKind,
+    /// Everything from the keyword up to the opening brace, whitespace collapsed.
+    pub header: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    /// Byte range of the body, between the braces.
+    pub body: Range<usize>,
+}
+
+impl FunctionSpan {
+    pub fn contains_line(&self, line: u32) -> bool {
+        (self.start_line..=self.end_line).contains(&line)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StateVariable {
+    pub name: String,
+    pub type_name: String,
+    pub constant: bool,
+    pub immutable: bool,
+    pub initializer: Option<String>,
+    pub line: u32,
+}
+
+/// Contract-level declarations found by a brace-matching scan. This is not a parser: it is
+/// enough to tell which function a line belongs to and what a state variable was declared as.
+#[derive(Debug, Clone, Default)]
+pub struct SourceOutline {
+    pub functions: Vec<FunctionSpan>,
+    pub state_variables: Vec<StateVariable>,
+}
+
+const VARIABLE_KEYWORDS: &[&str] = &["public", "private", "internal", "constant", "immutable", "override", "transient"];
+const NON_VARIABLE_KEYWORDS: &[&str] = &["function", "event", "error", "using", "modifier", "struct", "enum", "type", "import", "pragma", "constructor", "fallback", "receive", "return"];
+
+impl SourceOutline {
+    pub fn parse(source: &str) -> Self {
+        let masked = mask_comments_and_strings(source);
+        let bytes = masked.as_bytes();
+        let line_of = |offset: usize| line_at(&masked, offset);
+
+        let mut outline = SourceOutline::default();
+        // (kind, name, header, header offset, body start, depth of the opening brace)
+        let mut open: Vec<(FunctionKind, String, String, usize, usize, usize)> = Vec::new();
+        let mut depth = 0usize;
+        let mut stmt_start = 0usize;
+        for (i, c) in bytes.iter().enumerate() {
+            match c {
+                b'{' => {
+                    if depth <= 1 {
+                        let header_start = stmt_start + (masked[stmt_start..i].len() - masked[stmt_start..i].trim_start().len());
+                        let header = collapse_whitespace(&masked[stmt_start..i]);
+                        if let Some((kind, name)) = function_header(&header) {
+                            open.push((kind, name, header, header_start, i + 1, depth));
+                        }
+                    }
+                    depth += 1;
+                    stmt_start = i + 1;
+                }
+                b'}' => {
+                    depth = depth.saturating_sub(1);
+                    if open.last().is_some_and(|f| f.5 == depth) {
+                        let (kind, name, header, header_start, body_start, _) = open.pop().unwrap();
+                        outline.functions.push(FunctionSpan { name, kind, header, start_line: line_of(header_start), end_line: line_of(i), body: body_start..i });
+                    }
+                    stmt_start = i + 1;
+                }
+                b';' => {
+                    if depth == 1 {
+                        if let Some(mut variable) = state_variable(&masked[stmt_start..i]) {
+                            let offset = stmt_start + (masked[stmt_start..i].len() - masked[stmt_start..i].trim_start().len());
+                            variable.line = line_of(offset);
+                            outline.state_variables.push(variable);
+                        }
+                    }
+                    stmt_start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        outline.functions.sort_by_key(|f| f.body.start);
+        outline
+    }
+
+    /// The innermost function whose span contains the line.
+    pub fn function_at_line(&self, line: u32) -> Option<&FunctionSpan> {
+        self.functions.iter().filter(|f| f.contains_line(line)).min_by_key(|f| f.end_line - f.start_line)
+    }
+
+    pub fn state_variable(&self, name: &str) -> Option<&StateVariable> {
+        self.state_variables.iter().find(|v| v.name == name)
+    }
+
+    /// The value of an integer literal, or of a constant declared with one.
+    pub fn resolve_uint(&self, expr: &str) -> Option<u64> {
+        parse_uint_literal(expr).or_else(|| {
+            self.state_variable(expr.trim())
+                .filter(|v| v.constant)
+                .and_then(|v| v.initializer.as_deref())
+                .and_then(parse_uint_literal)
+        })
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn function_header(header: &str) -> Option<(FunctionKind, String)> {
+    let keyword = header.split(|c: char| !c.is_ascii_alphanumeric() && c != '_').next()?;
+    let kind = match keyword {
+        "function" => FunctionKind::Function,
+        "constructor" => FunctionKind::Constructor,
+        "modifier" => FunctionKind::Modifier,
+        "fallback" => FunctionKind::Fallback,
+        "receive" => FunctionKind::Receive,
+        _ => return None,
+    };
+    let name = match kind {
+        FunctionKind::Function | FunctionKind::Modifier => header[keyword.len()..]
+            .trim_start()
+            .split(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '$')
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        _ => keyword.to_string(),
+    };
+    Some((kind, name))
+}
+
+fn state_variable(statement: &str) -> Option<StateVariable> {
+    let statement = collapse_whitespace(statement);
+    let first = statement.split(|c: char| !c.is_ascii_alphanumeric() && c != '_').next()?;
+    if first.is_empty() || NON_VARIABLE_KEYWORDS.contains(&first) {
+        return None;
+    }
+
+    // The first `=` that is not part of `==`, `=>`, `<=`, `>=` or `!=`.
+    let bytes = statement.as_bytes();
+    let assign = (0..bytes.len()).find(|&i| {
+        bytes[i] == b'='
+            && bytes.get(i + 1).is_none_or(|n| *n != b'=' && *n != b'>')
+            && (i == 0 || !matches!(bytes[i - 1], b'=' | b'!' | b'<' | b'>'))
+    });
+    let (declaration, initializer) = match assign {
+        Some(i) => (statement[..i].trim(), Some(statement[i + 1..].trim().to_string())),
+        None => (statement.as_str(), None),
+    };
+
+    let tokens: Vec<&str> = declaration.split(' ').collect();
+    let name = *tokens.last()?;
+    if tokens.len() < 2 || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$') {
+        return None;
+    }
+    let type_end = tokens.iter().position(|t| VARIABLE_KEYWORDS.contains(t)).unwrap_or(tokens.len() - 1);
+    Some(StateVariable {
+        name: name.to_string(),
+        type_name: tokens[..type_end].join(" "),
+        constant: tokens.contains(&"constant"),
+        immutable: tokens.contains(&"immutable"),
+        initializer,
+        line: 0,
+    })
+}
+
+/// Byte ranges of inline `assembly { ... }` bodies in masked source.
+pub fn assembly_blocks(masked: &str) -> Vec<Range<usize>> {
+    let bytes = masked.as_bytes();
+    let mut blocks = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = masked[pos..].find("assembly") {
+        let start = pos + offset;
+        pos = start + "assembly".len();
+        let standalone = (start == 0 || !is_identifier_byte(bytes[start - 1])) && bytes.get(pos).is_none_or(|b| !is_identifier_byte(*b));
+        // Skip an optional dialect string and flags: `assembly "evm-assembly" ("memory-safe") {`.
+        let Some(open) = masked[pos..].find(['{', ';', '}']).map(|o| pos + o).filter(|o| bytes[*o] == b'{') else { continue };
+        if !standalone {
+            continue;
+        }
+        let mut depth = 0usize;
+        for (i, c) in bytes.iter().enumerate().skip(open) {
+            match c {
+                b'{' => depth += 1,
+                b'}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        blocks.push(open + 1..i);
+                        pos = i + 1;
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    blocks
+}
+
+/// 1-based line number of a byte offset.
+pub fn line_at(source: &str, offset: usize) -> u32 {
+    (source.as_bytes()[..offset].iter().filter(|b| **b == b'\n').count() + 1) as u32
+}
+
+/// Index of the bracket closing the one at `open`.
+pub fn matching_close(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in text.bytes().enumerate().skip(open) {
+        match c {
+            b'(' | b'{' | b'[' => depth += 1,
+            b')' | b'}' | b']' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Ranges of the top-level, comma-separated expressions within `range`, trimmed; empty ones are dropped.
+pub fn split_arguments(text: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    let bytes = text.as_bytes();
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut part_start = range.start;
+    for i in range.clone() {
+        match bytes[i] {
+            b'(' | b'{' | b'[' => depth += 1,
+            b')' | b'}' | b']' => depth = depth.saturating_sub(1),
+            b',' if depth == 0 => {
+                parts.push(part_start..i);
+                part_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(part_start..range.end);
+    parts.into_iter()
+        .map(|p| {
+            let slice = &text[p.clone()];
+            let start = p.start + (slice.len() - slice.trim_start().len());
+            start..start + slice.trim().len()
+        })
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// Ranges of the statements within `range` (usually a function body), split at `;` and at braces
+/// outside parentheses, so a statement spanning several lines is one range. Ranges are trimmed;
+/// empty ones are dropped.
+pub fn statements(text: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    let bytes = text.as_bytes();
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut part_start = range.start;
+    for i in range.clone() {
+        match bytes[i] {
+            b'(' | b'[' => depth += 1,
+            b')' | b']' => depth = depth.saturating_sub(1),
+            b';' | b'{' | b'}' if depth == 0 => {
+                parts.push(part_start..i);
+                part_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(part_start..range.end);
+    parts.into_iter()
+        .map(|p| {
+            let slice = &text[p.clone()];
+            let start = p.start + (slice.len() - slice.trim_start().len());
+            start..start + slice.trim().len()
+        })
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// The variable a statement assigns to (`x = ...`, `uint256 x = ...`, `x[i] += ...`), and the
+/// range of the assigned value within `statement`.
+pub fn assignment(statement: &str) -> Option<(&str, Range<usize>)> {
+    let bytes = statement.as_bytes();
+    let mut depth = 0usize;
+    let assign = (0..bytes.len()).find(|&i| {
+        match bytes[i] {
+            b'(' | b'[' => depth += 1,
+            b')' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        depth == 0
+            && bytes[i] == b'='
+            && bytes.get(i + 1).is_none_or(|n| *n != b'=' && *n != b'>')
+            && (i == 0 || !matches!(bytes[i - 1], b'=' | b'!' | b'<' | b'>'))
+    })?;
+    let mut target = statement[..assign].trim_end_matches(['+', '-', '*', '/', '%', '|', '&', '^']).trim_end();
+    while target.ends_with(']') {
+        target = target[..target.rfind('[')?].trim_end();
+    }
+    let name_start = target.rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$')).map(|i| i + 1).unwrap_or(0);
+    let name = &target[name_start..];
+    (!name.is_empty() && !name.starts_with(|c: char| c.is_ascii_digit())).then_some((name, assign + 1..statement.len()))
+}
+
+fn is_identifier_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$'
+}
+
+/// Parses decimal (with `_` separators and `e` exponents) and hex integer literals.
+pub fn parse_uint_literal(literal: &str) -> Option<u64> {
+    let literal = literal.trim().replace('_', "");
+    if let Some(hex) = literal.strip_prefix("0x").or_else(|| literal.strip_prefix("0X")) {
+        return u64::from_str_radix(hex, 16).ok();
+    }
+    match literal.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => mantissa.parse::<u64>().ok()?.checked_mul(10u64.checked_pow(exponent.parse().ok()?)?),
+        None => literal.parse().ok(),
+    }
+}
+
+/// A solc version as (major, minor, patch).
+pub type SolcVersion = (u32, u32, u32);
+
+// Joins an operator to its version: `>= 0.8.0` becomes `>=0.8.0`.
+static PRAGMA_OPERATOR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(>=|<=|>|<|\^|~|=)\s+").unwrap());
+
+/// Lowest and highest compiler versions a pragma constraint allows. Only the first `||`
+/// alternative is considered. `None` if the constraint is malformed or allows no version.
+pub fn pragma_range(constraint: &str) -> Option<(SolcVersion, SolcVersion)> {
+    let normalized = PRAGMA_OPERATOR.replace_all(constraint.split("||").next()?, "$1");
+    let mut lowest = (0, 0, 0);
+    let mut highest = (u32::MAX, u32::MAX, u32::MAX);
+    for token in normalized.split_whitespace() {
+        let split = token.find(|c: char| c.is_ascii_digit())?;
+        let (op, version) = token.split_at(split);
+        let mut parts = version.split('.').map(|p| p.parse::<u32>().ok());
+        let v = (parts.next()??, parts.next().flatten().unwrap_or(0), parts.next().flatten().unwrap_or(0));
+        let below = |(a, b, c): SolcVersion| if c > 0 { (a, b, c - 1) } else if b > 0 { (a, b - 1, u32::MAX) } else { (a.saturating_sub(1), u32::MAX, u32::MAX) };
+        match op {
+            "^" if v.0 == 0 => { lowest = lowest.max(v); highest = highest.min(below((0, v.1.checked_add(1)?, 0))); }
+            "^" => { lowest = lowest.max(v); highest = highest.min(below((v.0.checked_add(1)?, 0, 0))); }
+            "~" => { lowest = lowest.max(v); highest = highest.min(below((v.0, v.1.checked_add(1)?, 0))); }
+            ">=" => lowest = lowest.max(v),
+            ">" => lowest = lowest.max((v.0, v.1, v.2.checked_add(1)?)),
+            "<=" => highest = highest.min(v),
+            "<" => highest = highest.min(below(v)),
+            "" | "=" => { lowest = lowest.max(v); highest = highest.min(v); }
+            _ => return None,
+        }
+    }
+    (lowest <= highest).then_some((lowest, highest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX: u32 = u32::MAX;
+
+    #[test]
+    fn caret_and_tilde_ranges() {
+        assert_eq!(pragma_range("^0.8.0"), Some(((0, 8, 0), (0, 8, MAX))));
+        assert_eq!(pragma_range("^1.2.3"), Some(((1, 2, 3), (1, MAX, MAX))));
+        assert_eq!(pragma_range("~0.8.4"), Some(((0, 8, 4), (0, 8, MAX))));
+        assert_eq!(pragma_range("0.8.19"), Some(((0, 8, 19), (0, 8, 19))));
+    }
+
+    #[test]
+    fn clauses_intersect() {
+        assert_eq!(pragma_range(">=0.7.0 <0.9.0"), Some(((0, 7, 0), (0, 8, MAX))));
+        assert_eq!(pragma_range("^0.8.0 <0.8.5"), Some(((0, 8, 0), (0, 8, 4))));
+        assert_eq!(pragma_range("> 0.8.0 <= 0.8.20"), Some(((0, 8, 1), (0, 8, 20))));
+        assert_eq!(pragma_range(">=0.8.20 <0.8.0"), None);
+    }
+
+    #[test]
+    fn only_first_alternative_is_used() {
+        assert_eq!(pragma_range("^0.8.0 || ^0.7.0"), Some(((0, 8, 0), (0, 8, MAX))));
+    }
+
+    #[test]
+    fn malformed_constraints_are_rejected() {
+        assert_eq!(pragma_range("solidity"), None);
+        assert_eq!(pragma_range("!0.8.0"), None);
+    }
+
+    #[test]
+    fn statements_span_lines_and_keep_parentheses_whole() {
+        let body = "uint256 id = block.chainid;\n    sep = keccak256(\n        abi.encode(id)\n    );\n    for (uint i = 0; i < n; i++) { total += i; }";
+        let parts: Vec<&str> = statements(body, 0..body.len()).into_iter().map(|r| &body[r]).collect();
+        assert_eq!(parts, ["uint256 id = block.chainid", "sep = keccak256(\n        abi.encode(id)\n    )", "for (uint i = 0; i < n; i++)", "total += i"]);
+    }
+
+    #[test]
+    fn assignment_targets() {
+        let target = |s: &str| assignment(s).map(|(name, value)| (name.to_string(), s[value].trim().to_string()));
+        assert_eq!(target("uint256 id = block.chainid"), Some(("id".to_string(), "block.chainid".to_string())));
+        assert_eq!(target("balances[msg.sender] += amount"), Some(("balances".to_string(), "amount".to_string())));
+        assert_eq!(target("require(a == b)"), None);
+        assert_eq!(target("if (a <= b)"), None);
+        assert_eq!(target("foo(x = 1)"), None);
+    }
+
+    #[test]
+    fn out_of_range_versions_are_rejected() {
+        assert_eq!(pragma_range("^0.4294967295.0"), None);
+        assert_eq!(pragma_range("^4294967295.0.0"), None);
+        assert_eq!(pragma_range("~0.4294967295.0"), None);
+        assert_eq!(pragma_range(">1.2.4294967295"), None);
+    }
+}
@@ -0,0 +1,64 @@
+//! P-Chain staking rules: the bounds the P-Chain enforces on stake amounts, durations and
+//! delegation fees.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+// Primary Network values for mainnet and Fuji. Subnets with their own staking rules pass a
+// profile in the same format with the job.
+const BUILTIN_RULES: &str = include_str!("../registry/staking_rules.json");
+
+/// Delegation fees are expressed in parts per million: 20000 is 2%.
+pub const DELEGATION_FEE_DENOMINATOR: u64 = 1_000_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StakingRules {
+    /// Network name, matching the address registry, e.g. `fuji`.
+    #[serde(default)]
+    pub network: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Stake amounts are in nAVAX (10^-9 AVAX).
+    pub min_validator_stake: u64,
+    pub max_validator_stake: u64,
+    pub min_delegator_stake: u64,
+    /// Durations are in seconds.
+    pub min_stake_duration: u64,
+    pub max_stake_duration: u64,
+    pub min_delegation_fee: u64,
+    /// A validator's weight, its stake plus delegations, is capped at this multiple of its stake.
+    pub max_validator_weight_factor: u64,
+}
+
+#[derive(Deserialize)]
+struct RulesFile {
+    networks: Vec<StakingRules>,
+}
+
+impl StakingRules {
+    pub fn builtin() -> Vec<Self> {
+        serde_json::from_str::<RulesFile>(BUILTIN_RULES).expect("built-in staking rules are valid").networks
+    }
+
+    /// The built-in rules for a network name or alias.
+    pub fn for_network(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        Self::builtin().into_iter().find(|r| r.network == name || r.aliases.contains(&name))
+    }
+
+    pub fn mainnet() -> Self {
+        Self::for_network("mainnet").expect("built-in staking rules include mainnet")
+    }
+
+    /// `base` with the given fields (same camelCase keys) replaced. Without a base, the
+    /// overrides must name every rule.
+    pub fn with_overrides(base: Option<&Self>, overrides: &Map<String, Value>) -> Result<Self, String> {
+        let mut rules = match base.map(serde_json::to_value) {
+            Some(Ok(Value::Object(map))) => map,
+            _ => Map::new(),
+        };
+        rules.extend(overrides.iter().map(|(k, v)| (k.clone(), v.clone())));
+        serde_json::from_value(Value::Object(rules)).map_err(|e| format!("invalid staking rules: {}", e))
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use sentinel_common::solidity::{matching_close, split_arguments, FunctionKind, FunctionSpan, SourceOutline};
+
+// Modifiers provided by OpenZeppelin bases, which are usually imported rather than in the source.
+const INHERITED_MODIFIERS: &[(&str, &str)] = &[("onlyOwner", "Ownable"), ("onlyRole", "AccessControl"), ("restricted", "AccessManaged")];
+
+// `only*` modifiers of OpenZeppelin bases that restrict the calling context, not the caller.
+const NON_CALLER_MODIFIERS: &[&str] = &["onlyInitializing", "onlyProxy", "onlyDelegateCall"];
+
+// Internal checks the same bases expose for use in function bodies.
+const INHERITED_CHECKS: &[&str] = &["_checkOwner", "_checkRole", "_checkCanCall"];
+
+/// How a function restricts its callers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Guard {
+    /// A modifier whose body checks the sender, or an `only*` modifier declared outside the source
+    /// and not known to check something other than the caller.
+    Modifier(String),
+    /// A modifier of an inherited OpenZeppelin base, e.g. `onlyOwner` from `Ownable`.
+    Inherited { modifier: String, base: String },
+    /// A sender check in the body: `require(msg.sender == owner)`, `if (msg.sender != owner) revert ...;`,
+    /// `_checkOwner()` or a call to a helper doing one of these.
+    InBody,
+}
+
+impl Guard {
+    pub fn describe(&self) -> String {
+        match self {
+            Guard::Modifier(modifier) => format!("the `{}` modifier", modifier),
+            Guard::Inherited { modifier, base } => format!("the `{}` modifier inherited from `{}`", modifier, base),
+            Guard::InBody => "a sender check in its body".to_string(),
+        }
+    }
+}
+
+/// Which modifiers and helpers of the source restrict the caller.
+pub struct AccessControl<'a> {
+    masked: &'a str,
+    // Modifiers declared in the source -> whether their body checks the sender.
+    modifiers: HashMap<String, bool>,
+    // Non-public functions whose body checks the sender, e.g. `_onlyOperator()`.
+    guard_helpers: Vec<String>,
+    // Bases the contracts inherit from.
+    bases: Vec<String>,
+}
+
+impl<'a> AccessControl<'a> {
+    pub fn analyze(masked: &'a str, outline: &'a SourceOutline) -> Self {
+        let inheritance = Regex::new(r"\b(?:abstract\s+)?contract\s+[A-Za-z_$][\w$]*\s+is\s+([^{]+)\{").unwrap();
+        let bases = inheritance.captures_iter(masked)
+            .flat_map(|c| c[1].split(',').map(|b| b.split('(').next().unwrap_or_default().trim().to_string()).collect::<Vec<_>>())
+            .collect();
+        let mut access = AccessControl { masked, modifiers: HashMap::new(), guard_helpers: Vec::new(), bases };
+
+        // A modifier or helper can delegate its check to another one, so iterate until nothing changes.
+        loop {
+            let mut changed = false;
+            for function in &outline.functions {
+                let body = &masked[function.body.clone()];
+                match function.kind {
+                    FunctionKind::Modifier => {
+                        let guards = access.checks_sender(body) || access.header_guard(&function.header).is_some();
+                        if access.modifiers.insert(function.name.clone(), guards) != Some(guards) {
+                            changed = true;
+                        }
+                    }
+                    FunctionKind::Function if !is_entry_point(function) && !access.guard_helpers.contains(&function.name) && access.checks_sender(body) => {
+                        access.guard_helpers.push(function.name.clone());
+                        changed = true;
+                    }
+                    _ => {}
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        access
+    }
+
+    /// How the function restricts its callers, if it does.
+    pub fn guard(&self, function: &FunctionSpan) -> Option<Guard> {
+        self.header_guard(&function.header).or_else(|| self.checks_sender(&self.masked[function.body.clone()]).then_some(Guard::InBody))
+    }
+
+    fn header_guard(&self, header: &str) -> Option<Guard> {
+        // Modifiers follow the parameter list and come before `returns (...)`.
+        let open = header.find('(')?;
+        let close = matching_close(header, open).unwrap_or(header.len() - 1);
+        let tail = &header[close + 1..];
+        let tail = tail.split(" returns").next().unwrap_or(tail);
+        let identifier = Regex::new(r"[A-Za-z_$][\w$]*").unwrap();
+        let guard = identifier.find_iter(tail).map(|m| m.as_str()).find_map(|name| match self.modifiers.get(name) {
+            Some(true) => Some(Guard::Modifier(name.to_string())),
+            Some(false) => None,
+            None => match INHERITED_MODIFIERS.iter().find(|(modifier, _)| *modifier == name) {
+                Some((modifier, base)) => Some(match self.bases.iter().find(|b| b.starts_with(base)) {
+                    Some(inherited) => Guard::Inherited { modifier: modifier.to_string(), base: inherited.clone() },
+                    None => Guard::Modifier(name.to_string()),
+                }),
+                None => (name.starts_with("only") && !NON_CALLER_MODIFIERS.contains(&name)).then(|| Guard::Modifier(name.to_string())),
+            },
+        });
+        guard
+    }
+
+    /// Whether a body reverts for unauthorized senders. Conditions that merely use the sender,
+    /// such as `require(balances[msg.sender] >= amount)`, or only rule out contract callers, such
+    /// as `require(msg.sender == tx.origin)`, do not count.
+    fn checks_sender(&self, body: &str) -> bool {
+        let sender = r"(?:msg\s*\.\s*sender\b|_msgSender\s*\(\s*\))";
+        let comparison = Regex::new(&format!(r"{sender}\s*[!=]=|[!=]=\s*{sender}|\bhasRole\s*\(")).unwrap();
+        let origin = Regex::new(&format!(r"{sender}\s*[!=]=\s*tx\s*\.\s*origin\b|\btx\s*\.\s*origin\s*[!=]=\s*{sender}")).unwrap();
+        // `require(operators[msg.sender])`, `if (!isOperator(msg.sender))`.
+        let lookup = Regex::new(&format!(r"^!?\s*[A-Za-z_$][\w$.]*\s*[\[(]\s*{sender}\s*[\])]$")).unwrap();
+        let is_check = |condition: &str| comparison.is_match(&origin.replace_all(condition, "")) || lookup.is_match(condition.trim());
+
+        let assertion = Regex::new(r"\b(require|assert)\s*\(").unwrap();
+        let asserted = assertion.find_iter(body).any(|m| {
+            let Some(close) = matching_close(body, m.end() - 1) else { return false };
+            split_arguments(body, m.end()..close).first().is_some_and(|condition| is_check(&body[condition.clone()]))
+        });
+        if asserted {
+            return true;
+        }
+
+        // `if (msg.sender != owner) revert Unauthorized();`, with or without braces.
+        let condition = Regex::new(r"\bif\s*\(").unwrap();
+        let reverts = Regex::new(r"^\s*\{?\s*revert\b").unwrap();
+        if condition.find_iter(body).any(|m| matching_close(body, m.end() - 1).is_some_and(|close| is_check(&body[m.end()..close]) && reverts.is_match(&body[close + 1..]))) {
+            return true;
+        }
+
+        let helpers: Vec<String> = INHERITED_CHECKS.iter().copied().chain(self.guard_helpers.iter().map(String::as_str)).map(regex::escape).collect();
+        Regex::new(&format!(r"\b({})\s*\(", helpers.join("|"))).unwrap().is_match(body)
+    }
+}
+
+/// Public and external functions, and `receive`/`fallback`.
+pub fn is_entry_point(function: &FunctionSpan) -> bool {
+    match function.kind {
+        FunctionKind::Function => function.header.split(|c: char| !c.is_ascii_alphanumeric()).any(|w| w == "public" || w == "external"),
+        FunctionKind::Receive | FunctionKind::Fallback => true,
+        FunctionKind::Constructor | FunctionKind::Modifier => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sentinel_common::solidity::mask_comments_and_strings;
+
+    fn guards(source: &str) -> Vec<(String, Option<Guard>)> {
+        let masked = mask_comments_and_strings(source);
+        let outline = SourceOutline::parse(source);
+        let access = AccessControl::analyze(&masked, &outline);
+        outline.functions.iter().filter(|f| f.kind == FunctionKind::Function).map(|f| (f.name.clone(), access.guard(f))).collect()
+    }
+
+    #[test]
+    fn unresolved_only_modifiers_guard_unless_they_restrict_the_context() {
+        let source = "contract A is Initializable, UUPSUpgradeable {\n    function a() external onlyOperator {}\n    function b() external onlyInitializing {}\n    function c() external onlyProxy {}\n    function d() external onlyOwner {}\n}\n";
+        assert_eq!(guards(source), vec![
+            ("a".to_string(), Some(Guard::Modifier("onlyOperator".to_string()))),
+            ("b".to_string(), None),
+            ("c".to_string(), None),
+            ("d".to_string(), Some(Guard::Modifier("onlyOwner".to_string()))),
+        ]);
+    }
+
+    #[test]
+    fn origin_comparison_is_not_a_sender_check() {
+        let source = "contract A {\n    address owner;\n    modifier noContracts() { require(msg.sender == tx.origin); _; }\n    function a() external noContracts {}\n    function b() external { require(tx.origin == msg.sender, \"eoa\"); }\n    function c() external { require(msg.sender == tx.origin && msg.sender == owner); }\n    function d() external { if (msg.sender != owner) revert(); }\n}\n";
+        assert_eq!(guards(source), vec![
+            ("a".to_string(), None),
+            ("b".to_string(), None),
+            ("c".to_string(), Some(Guard::InBody)),
+            ("d".to_string(), Some(Guard::InBody)),
+        ]);
+    }
+}
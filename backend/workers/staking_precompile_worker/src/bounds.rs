@@ -0,0 +1,256 @@
+use regex::Regex;
+
+use sentinel_common::solidity::{line_at, SourceOutline};
+use sentinel_common::staking_rules::{StakingRules, DELEGATION_FEE_DENOMINATOR};
+
+use crate::precompile_calls::{Method, PrecompileCall};
+use crate::PrecompileIssue;
+
+// The value sent with a call is in wei (10^-18 AVAX); the P-Chain counts nAVAX (10^-9 AVAX).
+const WEI_PER_NAVAX: u128 = 1_000_000_000;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The regexes `evaluate` and `local_value` use, compiled once per job.
+struct Patterns {
+    cast: Regex,
+    literal: Regex,
+    identifier: Regex,
+    assignment: Regex,
+}
+
+impl Patterns {
+    fn new() -> Self {
+        Patterns {
+            cast: Regex::new(r"^(?:uint\d*)?\s*\((.*)\)$").unwrap(),
+            literal: Regex::new(r"^((?:0x[0-9a-fA-F_]+)|(?:[0-9_]+(?:\.[0-9_]+)?(?:[eE][0-9]+)?))\s*(wei|gwei|ether|seconds|minutes|hours|days|weeks)?$").unwrap(),
+            identifier: Regex::new(r"^[A-Za-z_$][\w$]*$").unwrap(),
+            assignment: Regex::new(r"\b([A-Za-z_$][\w$]*)\s*=\s*([^=;][^;]*);").unwrap(),
+        }
+    }
+}
+
+/// Checks the stake amounts, durations and delegation fees a contract passes to staking
+/// precompiles against the network's P-Chain rules, where they are literals or constants.
+pub fn report_bounds(calls: &[PrecompileCall], masked: &str, outline: &SourceOutline, rules: &StakingRules, issues: &mut Vec<PrecompileIssue>) {
+    let network = if rules.network.is_empty() { "the target network".to_string() } else { format!("`{}`", rules.network) };
+    // Known validator and delegator stakes, in nAVAX, for the weight factor check.
+    let mut validator_stakes: Vec<(u32, u128)> = Vec::new();
+    let mut delegator_stakes: Vec<(u32, u128)> = Vec::new();
+    let patterns = Patterns::new();
+
+    for call in calls {
+        let (Method::Known(function) | Method::Mismatch { function, .. }) = &call.method else { continue };
+        let scope = outline.function_at_line(call.line).map(|f| f.body.clone());
+        let resolve = |name: &str| local_value(masked, &patterns, scope.clone(), name, call.line).or_else(|| constant_value(outline, name));
+
+        let mut values: Vec<(&str, String, u128)> = function.parameters.iter().zip(&call.arguments)
+            .filter_map(|(role, argument)| Some((role.as_str(), argument.clone(), evaluate(argument, &patterns, &resolve, 0)?)))
+            .collect();
+        if let (Some(role), Some(value)) = (&function.value, &call.value) {
+            if let Some(wei) = evaluate(value, &patterns, &resolve, 0) {
+                values.push((role.as_str(), value.clone(), wei / WEI_PER_NAVAX));
+            }
+        }
+
+        for (role, expression, value) in values {
+            let problem = match role {
+                "validatorStake" | "delegatorStake" => {
+                    let (kind, min) = if role == "validatorStake" { ("validator", rules.min_validator_stake) } else { ("delegator", rules.min_delegator_stake) };
+                    if role == "validatorStake" { validator_stakes.push((call.line, value)) } else { delegator_stakes.push((call.line, value)) }
+                    if value < min as u128 {
+                        Some(("Stake Amount Out of Bounds", format!("stakes {} AVAX as a {}, below the minimum {} stake of {} AVAX on {}", avax(value), kind, kind, avax(min as u128), network)))
+                    } else if value > rules.max_validator_stake as u128 {
+                        Some(("Stake Amount Out of Bounds", format!("stakes {} AVAX, above the maximum stake of {} AVAX on {}", avax(value), avax(rules.max_validator_stake as u128), network)))
+                    } else {
+                        None
+                    }
+                }
+                "stakeDuration" if value < rules.min_stake_duration as u128 => {
+                    Some(("Stake Duration Out of Bounds", format!("stakes for {}, below the minimum staking duration of {} on {}", duration(value), duration(rules.min_stake_duration as u128), network)))
+                }
+                "stakeDuration" if value > rules.max_stake_duration as u128 => {
+                    Some(("Stake Duration Out of Bounds", format!("stakes for {}, above the maximum staking duration of {} on {}", duration(value), duration(rules.max_stake_duration as u128), network)))
+                }
+                "delegationFee" if value < rules.min_delegation_fee as u128 || value > DELEGATION_FEE_DENOMINATOR as u128 => {
+                    Some(("Delegation Fee Out of Bounds", format!("sets a delegation fee of {}%, outside the allowed range of {}% to 100% on {}", percent(value), percent(rules.min_delegation_fee as u128), network)))
+                }
+                _ => None,
+            };
+            let Some((issue_type, problem)) = problem else { continue };
+            issues.push(PrecompileIssue {
+                line: call.line,
+                issue_type: issue_type.to_string(),
+                description: format!("`{}` {} (`{}`), so the P-Chain rejects the transaction.", call.expression, problem, expression),
+                recommendation: "Validate staking parameters against the network's rules before calling the precompile, and make network-specific values configurable rather than constants.".to_string(),
+            });
+        }
+    }
+
+    // Delegations to a validator may add up to (factor - 1) times its own stake, so a delegation
+    // above that for the largest validator the contract creates cannot fit any of them.
+    let Some(&(validator_line, largest)) = validator_stakes.iter().max_by_key(|(_, stake)| *stake) else { return };
+    let cap = largest.saturating_mul(rules.max_validator_weight_factor.saturating_sub(1) as u128);
+    for &(line, stake) in delegator_stakes.iter().filter(|(_, stake)| *stake > cap) {
+        issues.push(PrecompileIssue {
+            line,
+            issue_type: "Validator Weight Factor Exceeded".to_string(),
+            description: format!("The contract delegates {} AVAX, more than {} times the {} AVAX it stakes as a validator (line {}). If both target the same validator, the delegation exceeds the maximum weight and is rejected.", avax(stake), rules.max_validator_weight_factor.saturating_sub(1), avax(largest), validator_line),
+            recommendation: "Cap delegations at the validator's remaining capacity: (weight factor - 1) × its stake, minus existing delegations.".to_string(),
+        });
+    }
+}
+
+/// Integer value of a constant expression: literals with optional ether or time units,
+/// `uintN(...)` casts, `+` and `*`, and names `resolve` maps to an expression.
+fn evaluate(expression: &str, patterns: &Patterns, resolve: &dyn Fn(&str) -> Option<String>, depth: usize) -> Option<u128> {
+    if depth > 4 {
+        return None;
+    }
+    let expression = expression.trim();
+    let sum = split_top_level(expression, '+');
+    if sum.len() > 1 {
+        return sum.iter().try_fold(0u128, |total, term| total.checked_add(evaluate(term, patterns, resolve, depth)?));
+    }
+    let product = split_top_level(expression, '*');
+    if product.len() > 1 {
+        return product.iter().try_fold(1u128, |total, factor| total.checked_mul(evaluate(factor, patterns, resolve, depth)?));
+    }
+
+    if let Some(c) = patterns.cast.captures(expression) {
+        return evaluate(&c[1], patterns, resolve, depth);
+    }
+    if let Some(c) = patterns.literal.captures(expression) {
+        let unit: u128 = match c.get(2).map(|m| m.as_str()) {
+            None | Some("wei") | Some("seconds") => 1,
+            Some("gwei") => 1_000_000_000,
+            Some("ether") => 1_000_000_000_000_000_000,
+            Some("minutes") => 60,
+            Some("hours") => 3_600,
+            Some("days") => SECONDS_PER_DAY as u128,
+            _ => 7 * SECONDS_PER_DAY as u128,
+        };
+        return parse_number(&c[1], unit);
+    }
+    if patterns.identifier.is_match(expression) {
+        return evaluate(&resolve(expression)?, patterns, resolve, depth + 1);
+    }
+    None
+}
+
+/// A number literal times its unit; fractional literals such as `0.5 ether` are allowed when
+/// the product is whole.
+fn parse_number(literal: &str, unit: u128) -> Option<u128> {
+    let literal = literal.replace('_', "");
+    if let Some(hex) = literal.strip_prefix("0x") {
+        return u128::from_str_radix(hex, 16).ok()?.checked_mul(unit);
+    }
+    let (mantissa, exponent) = match literal.split_once(['e', 'E']) {
+        Some((m, e)) => (m.to_string(), e.parse::<u32>().ok()?),
+        None => (literal, 0),
+    };
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((&mantissa, ""));
+    let digits: u128 = format!("{}{}", whole, fraction).parse().ok()?;
+    let scale = 10u128.checked_pow(fraction.len() as u32)?;
+    let value = digits.checked_mul(unit)?.checked_mul(10u128.checked_pow(exponent)?)?;
+    value.is_multiple_of(scale).then(|| value / scale)
+}
+
+fn split_top_level(expression: &str, operator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in expression.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            // `1e+5` is not written in Solidity, so a sign is always an operator here.
+            _ if c == operator && depth == 0 => {
+                parts.push(&expression[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&expression[start..]);
+    parts
+}
+
+/// The initializer of a constant or immutable state variable.
+fn constant_value(outline: &SourceOutline, name: &str) -> Option<String> {
+    outline.state_variable(name).filter(|v| v.constant || v.immutable).and_then(|v| v.initializer.clone())
+}
+
+/// The value last assigned to a local before the call line, e.g. `uint64 duration = 14 days;`.
+fn local_value(masked: &str, patterns: &Patterns, scope: Option<std::ops::Range<usize>>, name: &str, before_line: u32) -> Option<String> {
+    let scope = scope?;
+    patterns.assignment.captures_iter(&masked[scope.clone()])
+        .filter(|c| &c[1] == name && line_at(masked, scope.start + c.get(0).unwrap().start()) <= before_line)
+        .last()
+        .map(|c| c[2].trim().to_string())
+}
+
+fn avax(navax: u128) -> String {
+    let whole = navax / WEI_PER_NAVAX;
+    let fraction = navax % WEI_PER_NAVAX;
+    if fraction == 0 { whole.to_string() } else { format!("{}.{}", whole, format!("{:09}", fraction).trim_end_matches('0')) }
+}
+
+fn duration(seconds: u128) -> String {
+    let day = SECONDS_PER_DAY as u128;
+    if seconds.is_multiple_of(day) { format!("{} days", seconds / day) } else { format!("{} seconds", seconds) }
+}
+
+fn percent(fee: u128) -> String {
+    let hundredths = fee * 10_000 / DELEGATION_FEE_DENOMINATOR as u128;
+    format!("{}.{:02}", hundredths / 100, hundredths % 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(expression: &str) -> Option<u128> {
+        let resolve = |name: &str| match name {
+            "MIN_STAKE" => Some("2_000 ether".to_string()),
+            "PERIOD" => Some("DAYS * 1 days".to_string()),
+            "DAYS" => Some("14".to_string()),
+            "LOOP" => Some("LOOP".to_string()),
+            _ => None,
+        };
+        evaluate(expression, &Patterns::new(), &resolve, 0)
+    }
+
+    #[test]
+    fn literals_with_units_and_separators() {
+        assert_eq!(parse_number("25", 1), Some(25));
+        assert_eq!(parse_number("1_000", 1), Some(1_000));
+        assert_eq!(parse_number("0x10", 1), Some(16));
+        assert_eq!(parse_number("1.5", 1_000), Some(1_500));
+        assert_eq!(parse_number("2e3", 1), Some(2_000));
+        assert_eq!(parse_number("2.5e1", 1), Some(25));
+    }
+
+    #[test]
+    fn fractions_that_do_not_divide_evenly_are_rejected() {
+        assert_eq!(parse_number("1.5", 1), None);
+        assert_eq!(parse_number("0.0001", 1_000), None);
+    }
+
+    #[test]
+    fn overflowing_literals_are_rejected() {
+        assert_eq!(parse_number("1e40", 1_000_000_000_000_000_000), None);
+        assert_eq!(parse_number("0xffffffffffffffffffffffffffffffffff", 1), None);
+    }
+
+    #[test]
+    fn expressions_resolve_through_constants() {
+        assert_eq!(value("2_000 ether"), Some(2_000 * 10u128.pow(18)));
+        assert_eq!(value("1.5 ether + 500 gwei"), Some(1_500_000_000_000_000_000 + 500_000_000_000));
+        assert_eq!(value("uint64(2 weeks)"), Some(14 * SECONDS_PER_DAY as u128));
+        assert_eq!(value("MIN_STAKE"), Some(2_000 * 10u128.pow(18)));
+        assert_eq!(value("PERIOD"), Some(14 * SECONDS_PER_DAY as u128));
+        assert_eq!(value("amount"), None);
+        assert_eq!(value("LOOP"), None);
+    }
+}
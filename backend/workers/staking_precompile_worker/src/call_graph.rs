@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+
+use regex::Regex;
+
+use sentinel_common::solidity::{FunctionKind, FunctionSpan, SourceOutline};
+
+use crate::access_control;
+
+/// Which functions and modifiers of the source call which, by name. Overloads are not told
+/// apart, so a call edges to every function of that name.
+pub struct CallGraph<'a> {
+    functions: &'a [FunctionSpan],
+    // Indices of the functions each function calls, or whose modifiers it applies.
+    callees: Vec<Vec<usize>>,
+}
+
+impl<'a> CallGraph<'a> {
+    pub fn build(masked: &str, outline: &'a SourceOutline) -> Self {
+        let functions = outline.functions.as_slice();
+        // `helper(...)` and `this.helper(...)`; other member calls go to other contracts.
+        let call = Regex::new(r"(?:^|[^\w$.]|\bthis\s*\.)\s*([A-Za-z_$][\w$]*)\s*\(").unwrap();
+        let identifier = Regex::new(r"[A-Za-z_$][\w$]*").unwrap();
+        let callees = functions.iter()
+            .map(|function| {
+                let body = &masked[function.body.clone()];
+                // Modifiers applied in the header, after the name and before `returns`.
+                let header = &function.header[function.header.find('(').unwrap_or(0)..];
+                let header = header.split(" returns").next().unwrap_or_default();
+                let mut names: Vec<&str> = call.captures_iter(body).map(|c| c.get(1).unwrap().as_str()).collect();
+                names.extend(identifier.find_iter(header).map(|m| m.as_str()));
+                functions.iter().enumerate()
+                    .filter(|(_, f)| f.body != function.body && names.contains(&f.name.as_str()))
+                    .filter(|(_, f)| matches!(f.kind, FunctionKind::Function | FunctionKind::Modifier))
+                    .map(|(i, _)| i)
+                    .collect()
+            })
+            .collect();
+        CallGraph { functions, callees }
+    }
+
+    /// `source` and every function or modifier it reaches, nearest first.
+    pub fn reachable_from(&self, source: &FunctionSpan) -> Vec<&'a FunctionSpan> {
+        let Some(start) = self.functions.iter().position(|f| f.body == source.body) else { return Vec::new() };
+        let mut order = vec![start];
+        let mut i = 0;
+        while i < order.len() {
+            for &callee in &self.callees[order[i]] {
+                if !order.contains(&callee) {
+                    order.push(callee);
+                }
+            }
+            i += 1;
+        }
+        order.into_iter().map(|i| &self.functions[i]).collect()
+    }
+
+    /// For each public or external entry point that reaches `target`, the shortest call path
+    /// from it to `target` (both included). Only functions accepted by `through` are followed,
+    /// so e.g. guarded functions can cut the paths that pass through them.
+    pub fn entry_paths(&self, target: &FunctionSpan, through: impl Fn(&FunctionSpan) -> bool) -> Vec<Vec<&'a FunctionSpan>> {
+        let Some(start) = self.functions.iter().position(|f| f.body == target.body) else { return Vec::new() };
+        if !through(&self.functions[start]) {
+            return Vec::new();
+        }
+        // Breadth-first over reversed edges; `next[i]` is the step from `i` towards the target.
+        let mut next: Vec<Option<usize>> = vec![None; self.functions.len()];
+        let mut visited = vec![false; self.functions.len()];
+        visited[start] = true;
+        let mut queue = VecDeque::from([start]);
+        let mut entries = Vec::new();
+        while let Some(current) = queue.pop_front() {
+            if access_control::is_entry_point(&self.functions[current]) {
+                entries.push(current);
+            }
+            for caller in 0..self.functions.len() {
+                if !visited[caller] && self.callees[caller].contains(&current) && through(&self.functions[caller]) {
+                    visited[caller] = true;
+                    next[caller] = Some(current);
+                    queue.push_back(caller);
+                }
+            }
+        }
+        entries.into_iter()
+            .map(|entry| {
+                let mut path = vec![&self.functions[entry]];
+                let mut step = entry;
+                while let Some(following) = next[step] {
+                    path.push(&self.functions[following]);
+                    step = following;
+                }
+                path
+            })
+            .collect()
+    }
+}
+
+/// `a` → `b` → `c`.
+pub fn describe_path(path: &[&FunctionSpan]) -> String {
+    path.iter().map(|f| format!("`{}`", f.name)).collect::<Vec<_>>().join(" → ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sentinel_common::solidity::mask_comments_and_strings;
+
+    const SOURCE: &str = "contract A {
+    modifier onlyOwner() { require(msg.sender == owner); _; }
+    function stake() external payable { _stake(); }
+    function stakeFor() public onlyOwner { this.route(); }
+    function route() public { _stake(); }
+    function _stake() internal { _call(); }
+    function _call() private {}
+    function unused() internal { _call(); }
+}
+";
+
+    fn paths(through: impl Fn(&FunctionSpan) -> bool) -> Vec<String> {
+        let masked = mask_comments_and_strings(SOURCE);
+        let outline = SourceOutline::parse(SOURCE);
+        let graph = CallGraph::build(&masked, &outline);
+        let target = outline.functions.iter().find(|f| f.name == "_call").unwrap();
+        graph.entry_paths(target, through).iter().map(|p| describe_path(p)).collect()
+    }
+
+    #[test]
+    fn shortest_path_from_each_entry_point() {
+        assert_eq!(paths(|_| true), [
+            "`stake` → `_stake` → `_call`",
+            "`route` → `_stake` → `_call`",
+            "`stakeFor` → `route` → `_stake` → `_call`",
+        ]);
+    }
+
+    #[test]
+    fn excluded_functions_cut_paths() {
+        assert_eq!(paths(|f| f.name != "route"), ["`stake` → `_stake` → `_call`"]);
+        assert!(paths(|f| f.name != "_call").is_empty());
+    }
+
+    #[test]
+    fn reachable_includes_applied_modifiers() {
+        let masked = mask_comments_and_strings(SOURCE);
+        let outline = SourceOutline::parse(SOURCE);
+        let graph = CallGraph::build(&masked, &outline);
+        let stake_for = outline.functions.iter().find(|f| f.name == "stakeFor").unwrap();
+        let names: Vec<&str> = graph.reachable_from(stake_for).iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["stakeFor", "onlyOwner", "route", "_stake", "_call"]);
+    }
+}
@@ -0,0 +1,141 @@
+use regex::Regex;
+
+use sentinel_common::solidity::{line_at, SourceOutline};
+
+use crate::precompile_calls::PrecompileCall;
+use crate::PrecompileIssue;
+
+/// The phases a stake goes through. The P-Chain processes the operation after the contract's
+/// transaction, so it can still fail after the precompile call returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Pending,
+    Active,
+    Ended,
+    Failed,
+}
+
+const PHASES: &[(Phase, &str, &str)] = &[
+    (Phase::Pending, "pending", r"(?i)^(pending|requested|initiated|submitted|queued|processing)"),
+    (Phase::Active, "active", r"(?i)^(active|staked|confirmed|validating|delegated|live)"),
+    (Phase::Ended, "ended", r"(?i)^(ended|completed|exited|withdrawn|unlocked|expired|removed|closed|matured|unstaked)"),
+    (Phase::Failed, "failed", r"(?i)^(failed|rejected|cancell?ed|reverted|aborted|refunded)"),
+];
+
+/// An enum that models the stake lifecycle, and the phase of each of its members.
+struct StateEnum {
+    name: String,
+    line: u32,
+    members: Vec<(String, Option<Phase>)>,
+}
+
+/// Checks that a contract staking through the precompiles tracks each stake through pending,
+/// active, ended and failed states, handles P-Chain failures, and does not count stake as
+/// active before the P-Chain confirms it.
+pub fn report_lifecycle(calls: &[PrecompileCall], masked: &str, outline: &SourceOutline, issues: &mut Vec<PrecompileIssue>) {
+    let Some(first_call) = calls.first() else { return };
+    let phases: Vec<(Phase, &str, Regex)> = PHASES.iter().map(|(phase, name, pattern)| (*phase, *name, Regex::new(pattern).unwrap())).collect();
+    let phase_of = |member: &str| phases.iter().find(|(_, _, p)| p.is_match(member)).map(|(phase, _, _)| *phase);
+
+    let declaration = Regex::new(r"\benum\s+([A-Za-z_$][\w$]*)\s*\{([^}]*)\}").unwrap();
+    let state_enum = declaration.captures_iter(masked)
+        .map(|c| StateEnum {
+            name: c[1].to_string(),
+            line: line_at(masked, c.get(0).unwrap().start()),
+            members: c[2].split(',').map(str::trim).filter(|m| !m.is_empty()).map(|m| (m.to_string(), phase_of(m))).collect(),
+        })
+        // The enum with the most lifecycle-like members, if it has at least two.
+        .filter(|e| e.members.iter().filter(|(_, p)| p.is_some()).count() >= 2)
+        .max_by_key(|e| e.members.iter().filter(|(_, p)| p.is_some()).count());
+
+    let Some(state_enum) = state_enum else {
+        issues.push(PrecompileIssue {
+            line: first_call.line,
+            issue_type: "Untracked Stake Lifecycle".to_string(),
+            description: "The contract stakes through a precompile but declares no state for its stakes (e.g. an enum with Pending, Active, Ended and Failed). The P-Chain processes the operation asynchronously, so the contract cannot tell submitted stakes from confirmed or failed ones.".to_string(),
+            recommendation: "Record each staking operation with an explicit status, set it to Pending when the precompile is called, and move it to Active, Failed or Ended only on confirmation from the P-Chain (e.g. a Warp message or a trusted relayer).".to_string(),
+        });
+        report_premature_accounting(calls, masked, outline, &[], issues);
+        return;
+    };
+
+    let missing: Vec<&str> = phases.iter()
+        .filter(|(phase, _, _)| !state_enum.members.iter().any(|(_, p)| *p == Some(*phase)))
+        .map(|(_, name, _)| *name)
+        .collect();
+    if !missing.is_empty() {
+        issues.push(PrecompileIssue {
+            line: state_enum.line,
+            issue_type: "Missing Stake State".to_string(),
+            description: format!("`{}` models the stake lifecycle but has no {} state.", state_enum.name, missing.join(", ")),
+            recommendation: "Model every phase of a staking operation: pending until the P-Chain confirms it, active while staked, ended once the stake is returned, and failed if the P-Chain rejects it.".to_string(),
+        });
+    }
+
+    // Transitions: `stake.status = Status.Active`. The first member is the zero value, so it needs no assignment.
+    let assignment = Regex::new(&format!(r"[^=!<>]=\s*{}\s*\.\s*([A-Za-z_$][\w$]*)", regex::escape(&state_enum.name))).unwrap();
+    let assigned: Vec<(String, usize)> = assignment.captures_iter(masked).map(|c| (c[1].to_string(), c.get(0).unwrap().start())).collect();
+    for (member, phase) in state_enum.members.iter().skip(1).filter(|(_, p)| p.is_some()) {
+        if assigned.iter().any(|(m, _)| m == member) {
+            continue;
+        }
+        let consequence = match phase {
+            Some(Phase::Active) => "stakes never become active",
+            Some(Phase::Ended) => "stakes never end, so their funds are never released",
+            Some(Phase::Failed) => "a rejected staking operation is never recorded, so its funds stay locked",
+            _ => "no operation ever enters it",
+        };
+        issues.push(PrecompileIssue {
+            line: state_enum.line,
+            issue_type: "Missing Stake Transition".to_string(),
+            description: format!("`{}.{}` is declared but never assigned: {}.", state_enum.name, member, consequence),
+            recommendation: "Add the transition, guarded by a check of the current state, in the function that handles the corresponding P-Chain outcome.".to_string(),
+        });
+    }
+
+    // A failure path: a Failed state that is assigned, or a function named for handling failures.
+    let failure_name = Regex::new(r"(?i)fail|reject|cancel|refund|recover|reclaim").unwrap();
+    let failure_handled = state_enum.members.iter().any(|(m, p)| *p == Some(Phase::Failed) && assigned.iter().any(|(a, _)| a == m))
+        || outline.functions.iter().any(|f| failure_name.is_match(&f.name));
+    if !failure_handled {
+        issues.push(PrecompileIssue {
+            line: first_call.line,
+            issue_type: "Unhandled Staking Failure".to_string(),
+            description: "Nothing handles a staking operation the P-Chain rejects after the precompile call returned (e.g. the validator left, or the stake fell outside the network's bounds). The funds sent stay accounted as staked.".to_string(),
+            recommendation: "Add a failure path, triggered by the P-Chain outcome, that marks the operation failed, returns or re-pools its funds, and reverses its accounting.".to_string(),
+        });
+    }
+
+    let activations: Vec<(String, usize)> = assigned.iter()
+        .filter(|(m, _)| state_enum.members.iter().any(|(member, p)| member == m && *p == Some(Phase::Active)))
+        .map(|(m, offset)| (format!("{}.{}", state_enum.name, m), *offset))
+        .collect();
+    report_premature_accounting(calls, masked, outline, &activations, issues);
+}
+
+/// Flags the functions that make a staking call and, in the same transaction, mark the stake
+/// active (one of `activations`, at their offsets) or add it to staked totals, before the
+/// P-Chain has confirmed it.
+fn report_premature_accounting(calls: &[PrecompileCall], masked: &str, outline: &SourceOutline, activations: &[(String, usize)], issues: &mut Vec<PrecompileIssue>) {
+    let staked_total = Regex::new(r"\b([A-Za-z_$][\w$]*)\s*(?:\[[^\]]*\]\s*)*\+=").unwrap();
+    let staked_name = Regex::new(r"(?i)staked|delegated|activeStake|totalStake|validatorStake").unwrap();
+    let mut reported: Vec<u32> = Vec::new();
+    for call in calls {
+        let Some(function) = outline.function_at_line(call.line) else { continue };
+        if reported.contains(&function.start_line) {
+            continue;
+        }
+        let counted = staked_total.captures_iter(&masked[function.body.clone()])
+            .find(|c| staked_name.is_match(&c[1]) && !c[1].to_lowercase().contains("pending") && outline.state_variable(&c[1]).is_some())
+            .map(|c| (format!("adds to `{}`", &c[1]), function.body.start + c.get(0).unwrap().start()));
+        let activated = activations.iter().find(|(_, offset)| function.body.contains(offset)).map(|(state, offset)| (format!("sets `{}`", state), *offset));
+        let Some((what, offset)) = activated.or(counted) else { continue };
+        reported.push(function.start_line);
+        issues.push(PrecompileIssue {
+            line: line_at(masked, offset),
+            issue_type: "Stake Counted Before Confirmation".to_string(),
+            description: format!("`{}` {} in the same transaction as the staking call, before the P-Chain has accepted the operation. If the P-Chain rejects it, the contract reports stake it does not have.", function.name, what),
+            recommendation: "Track the operation as pending (e.g. `pendingStake += amount`) and move it to the active totals only when the P-Chain confirms it.".to_string(),
+        });
+    }
+}
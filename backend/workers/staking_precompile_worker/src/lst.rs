@@ -0,0 +1,205 @@
+use regex::Regex;
+
+use sentinel_common::solidity::{self, line_at, matching_close, split_arguments, FunctionKind, FunctionSpan, SourceOutline};
+
+use crate::access_control;
+use crate::call_graph::CallGraph;
+use crate::PrecompileIssue;
+
+/// The parts of a liquid staking token: entry points that mint shares for deposits, entry
+/// points that burn them on redemption, and functions that price shares.
+struct LstPattern<'a> {
+    deposits: Vec<&'a FunctionSpan>,
+    redemptions: Vec<&'a FunctionSpan>,
+    rates: Vec<&'a FunctionSpan>,
+}
+
+/// Recognises a liquid staking token and reports the invariant hazards such contracts commonly
+/// have: donation attacks, first-depositor inflation, redemptions without slippage bounds and
+/// withdrawal queues processed in unbounded loops.
+pub fn report_lst(masked: &str, outline: &SourceOutline, graph: &CallGraph, issues: &mut Vec<PrecompileIssue>) {
+    let Some(lst) = recognise(masked, outline, graph) else { return };
+    let names = |functions: &[&FunctionSpan]| functions.iter().map(|f| format!("`{}`", f.name)).collect::<Vec<_>>().join(", ");
+    let rates = if lst.rates.is_empty() { String::new() } else { format!(", and shares are priced by {}", names(&lst.rates)) };
+    issues.push(PrecompileIssue {
+        line: lst.deposits[0].start_line,
+        issue_type: "Liquid Staking Token Pattern".to_string(),
+        description: format!("The contract looks like a liquid staking token: deposits through {} mint shares, redemptions through {} burn them{}.", names(&lst.deposits), names(&lst.redemptions), rates),
+        recommendation: "The share price must only move with staking rewards and slashing. Review the invariant checks below.".to_string(),
+    });
+
+    // Every function that prices shares, directly or on the deposit and redemption paths.
+    let mut pricing: Vec<&FunctionSpan> = Vec::new();
+    for &function in lst.deposits.iter().chain(&lst.redemptions).chain(&lst.rates) {
+        for reached in graph.reachable_from(function) {
+            if !pricing.iter().any(|f| f.body == reached.body) {
+                pricing.push(reached);
+            }
+        }
+    }
+
+    report_donation(masked, &pricing, &lst.rates, issues);
+    report_first_depositor(masked, &lst, graph, issues);
+    report_redeem_slippage(masked, &lst, graph, issues);
+    report_withdrawal_queues(masked, outline, issues);
+}
+
+fn recognise<'a>(masked: &str, outline: &'a SourceOutline, graph: &CallGraph<'a>) -> Option<LstPattern<'a>> {
+    let mint = Regex::new(r"\b_mint\s*\(|\.\s*mint\s*\(").unwrap();
+    let burn = Regex::new(r"\b_burn\s*\(|\.\s*(burn|burnFrom)\s*\(").unwrap();
+    let reaches = |function: &FunctionSpan, pattern: &Regex| graph.reachable_from(function).iter().any(|f| pattern.is_match(&masked[f.body.clone()]));
+
+    let entry_points: Vec<&FunctionSpan> = outline.functions.iter().filter(|f| f.kind == FunctionKind::Function && access_control::is_entry_point(f)).collect();
+    let deposits: Vec<&FunctionSpan> = entry_points.iter().copied().filter(|f| reaches(f, &mint)).collect();
+    let redemptions: Vec<&FunctionSpan> = entry_points.iter().copied().filter(|f| reaches(f, &burn)).collect();
+    if deposits.is_empty() || redemptions.is_empty() {
+        return None;
+    }
+    let rate_name = Regex::new(r"(?i)rate|^convertTo|^preview|pricePerShare|sharesFor|assetsFor|^getShares|^getPooled|^total(Pooled|Assets|Staked)").unwrap();
+    let rates = outline.functions.iter().filter(|f| f.kind == FunctionKind::Function && rate_name.is_match(&f.name)).collect();
+    Some(LstPattern { deposits, redemptions, rates })
+}
+
+/// `address(this).balance` in share pricing: anyone can raise it without minting shares, by a
+/// direct transfer or (if `receive` is missing) `selfdestruct`, to move the exchange rate. The
+/// balance prices shares when it feeds share math (the total supply, or a division), directly or
+/// through a local, or when a rate function returns it.
+fn report_donation(masked: &str, pricing: &[&FunctionSpan], rates: &[&FunctionSpan], issues: &mut Vec<PrecompileIssue>) {
+    let balance = Regex::new(r"\baddress\s*\(\s*this\s*\)\s*\.\s*balance\b").unwrap();
+    let word = Regex::new(r"[A-Za-z_$][\w$]*").unwrap();
+    let share_math = Regex::new(r"\btotalSupply\b|\btotalShares\b|/").unwrap();
+    let returned = Regex::new(r"^return\b").unwrap();
+    for function in pricing {
+        let is_rate = rates.iter().any(|r| r.body == function.body);
+        // Locals holding the balance, e.g. `uint256 pooled = address(this).balance;`.
+        let mut holders: Vec<&str> = Vec::new();
+        let priced = solidity::statements(masked, function.body.clone()).into_iter().find(|statement| {
+            let text = &masked[statement.clone()];
+            let reads_balance = balance.is_match(text) || word.find_iter(text).any(|w| holders.contains(&w.as_str()));
+            if !reads_balance {
+                return false;
+            }
+            // Liquidity guards such as `require(address(this).balance >= assets)` do not price anything.
+            if share_math.is_match(text) || (is_rate && returned.is_match(text)) {
+                return true;
+            }
+            if let Some((name, _)) = solidity::assignment(text) {
+                holders.push(name);
+            }
+            false
+        });
+        let Some(statement) = priced else { continue };
+        issues.push(PrecompileIssue {
+            line: line_at(masked, statement.start),
+            issue_type: "LST Donation Attack".to_string(),
+            description: format!("`{}` prices shares from `address(this).balance`. Anyone can raise it without minting shares (a direct transfer, or `selfdestruct`, which cannot be refused), inflating the exchange rate so later deposits round down to zero shares.", function.name),
+            recommendation: "Track pooled AVAX in a storage variable updated only on deposits, withdrawals and accounted rewards, and price shares from it rather than from the contract balance.".to_string(),
+        });
+    }
+}
+
+/// Share math that divides by the total supply on the deposit path, without virtual shares,
+/// dead shares or a minimum deposit to keep the first depositor from inflating the price.
+fn report_first_depositor(masked: &str, lst: &LstPattern, graph: &CallGraph, issues: &mut Vec<PrecompileIssue>) {
+    let mitigation = Regex::new(r"(?i)_decimalsOffset|MINIMUM_(LIQUIDITY|SHARES|DEPOSIT)|DEAD_?SHARES|_mint\s*\(\s*(address\s*\(\s*(0|0xdead)\w*\s*\)|DEAD)|(totalSupply\s*\(\s*\)|totalShares)\s*\+\s*(1\b|10\s*\*\*)|msg\.value\s*>=?\s*MIN").unwrap();
+    if mitigation.is_match(masked) {
+        return;
+    }
+    let share_math = Regex::new(r"\*\s*(totalSupply\s*\(\s*\)|totalShares)\s*\)?\s*/|\b(totalSupply\s*\(\s*\)|totalShares)\s*\*[^;]*/").unwrap();
+    for &deposit in &lst.deposits {
+        let Some((function, m)) = graph.reachable_from(deposit).into_iter().find_map(|f| share_math.find(&masked[f.body.clone()]).map(|m| (f, m))) else { continue };
+        issues.push(PrecompileIssue {
+            line: line_at(masked, function.body.start + m.start()),
+            issue_type: "First Depositor Share Inflation".to_string(),
+            description: format!("`{}` mints shares in proportion to the total supply with no virtual or dead shares. The first depositor can mint one share, donate to inflate its price, and make the next deposits round down to zero shares.", deposit.name),
+            recommendation: "Mint a small amount of dead shares on the first deposit, use virtual shares and assets (OpenZeppelin ERC4626's `_decimalsOffset`), or require a minimum first deposit.".to_string(),
+        });
+    }
+}
+
+/// Redemptions priced at the current exchange rate without a minimum amount out.
+fn report_redeem_slippage(masked: &str, lst: &LstPattern, graph: &CallGraph, issues: &mut Vec<PrecompileIssue>) {
+    let priced = Regex::new(r"(?i)totalSupply|totalShares|rate|convertTo|preview").unwrap();
+    let bound = Regex::new(r"(?i)^min|slippage|expected").unwrap();
+    for &redemption in &lst.redemptions {
+        let Some(open) = redemption.header.find('(') else { continue };
+        let close = matching_close(&redemption.header, open).unwrap_or(redemption.header.len());
+        let bounded = split_arguments(&redemption.header, open + 1..close).into_iter()
+            .filter_map(|p| redemption.header[p].split_whitespace().last().map(str::to_string))
+            .any(|name| bound.is_match(&name));
+        if bounded || !graph.reachable_from(redemption).iter().any(|f| priced.is_match(&masked[f.body.clone()])) {
+            continue;
+        }
+        issues.push(PrecompileIssue {
+            line: redemption.start_line,
+            issue_type: "Missing Redeem Slippage Bound".to_string(),
+            description: format!("`{}` burns shares at the current exchange rate but takes no minimum amount out, so a rate change (slashing, a donation, a front-run) between submission and execution goes unnoticed.", redemption.name),
+            recommendation: "Add a `minAmountOut` (and optionally a deadline) parameter and revert when the assets paid out fall below it.".to_string(),
+        });
+    }
+}
+
+/// Loops over a whole withdrawal queue that anyone can grow: once it is long enough, processing
+/// it runs out of gas and withdrawals are stuck.
+fn report_withdrawal_queues(masked: &str, outline: &SourceOutline, issues: &mut Vec<PrecompileIssue>) {
+    let queue_name = Regex::new(r"(?i)queue|request|withdraw|pending|unstake|unbond").unwrap();
+    let full_loop = Regex::new(r"\b(for|while)\s*\(").unwrap();
+    for queue in outline.state_variables.iter().filter(|v| v.type_name.ends_with("[]") && queue_name.is_match(&v.name)) {
+        let name = regex::escape(&queue.name);
+        if !Regex::new(&format!(r"\b{}\s*\.\s*push\s*\(", name)).unwrap().is_match(masked) {
+            continue;
+        }
+        let length = Regex::new(&format!(r"\b{}\s*\.\s*length\b", name)).unwrap();
+        for m in full_loop.find_iter(masked) {
+            let open = m.end() - 1;
+            let Some(close) = matching_close(masked, open) else { continue };
+            let header = &masked[open + 1..close];
+            // The bound is the queue length alone; `i < queue.length && i < max` is bounded.
+            if !length.is_match(header) || header.contains("&&") || header.contains("min(") {
+                continue;
+            }
+            let function = outline.function_at_line(line_at(masked, m.start())).map(|f| format!(" in `{}`", f.name)).unwrap_or_default();
+            issues.push(PrecompileIssue {
+                line: line_at(masked, m.start()),
+                issue_type: "Unbounded Withdrawal Queue".to_string(),
+                description: format!("The loop{} walks the whole `{}` queue, which grows with every request. Once it is long enough the loop exceeds the block gas limit and withdrawals can no longer be processed.", function, queue.name),
+                recommendation: "Process the queue in bounded batches from a stored head index, or let each user claim their own matured request.".to_string(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sentinel_common::solidity::mask_comments_and_strings;
+
+    const LST: &str = "contract Lst {\n    function deposit() external payable { _mint(msg.sender, msg.value); }\n    function redeem(uint256 shares, uint256 minOut) external { _burn(msg.sender, shares); }\n    BODY\n}\n";
+
+    fn donation_lines(body: &str) -> Vec<u32> {
+        let source = LST.replace("BODY", body);
+        let masked = mask_comments_and_strings(&source);
+        let outline = SourceOutline::parse(&source);
+        let graph = CallGraph::build(&masked, &outline);
+        let mut issues = Vec::new();
+        report_lst(&masked, &outline, &graph, &mut issues);
+        issues.iter().filter(|i| i.issue_type == "LST Donation Attack").map(|i| i.line).collect()
+    }
+
+    #[test]
+    fn liquidity_guard_is_not_share_pricing() {
+        assert!(donation_lines("function withdraw(uint256 assets) external { require(address(this).balance >= assets); _burn(msg.sender, assets); }").is_empty());
+    }
+
+    #[test]
+    fn balance_in_share_math_is_reported() {
+        assert_eq!(donation_lines("function sharesFor(uint256 amount) public view returns (uint256) { return amount * totalSupply() / address(this).balance; }"), vec![4]);
+        assert_eq!(donation_lines("function sharesFor(uint256 amount) public view returns (uint256) {\n        uint256 pooled = address(this).balance;\n        return amount * 1e18 / pooled;\n    }"), vec![6]);
+    }
+
+    #[test]
+    fn balance_returned_by_a_rate_function_is_reported() {
+        assert_eq!(donation_lines("function totalPooled() public view returns (uint256) { return address(this).balance; }"), vec![4]);
+        assert!(donation_lines("function balanceNow() public view returns (uint256) { return address(this).balance; }").is_empty());
+    }
+}
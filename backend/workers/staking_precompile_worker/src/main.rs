@@ -1,12 +1,39 @@
 use redis::{Commands, Client, Connection};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use regex::Regex;
 use std::collections::HashSet; // V3 FIX: Import HashSet for deduplication
+use sentinel_common::precompiles::PrecompileRegistry;
+use sentinel_common::solidity::{mask_comments_and_strings, FunctionKind, FunctionSpan, SourceOutline};
+use sentinel_common::staking_rules::StakingRules;
+
+mod access_control;
+mod bounds;
+mod call_graph;
+mod lifecycle;
+mod lst;
+mod node_ids;
+mod precompile_calls;
+mod return_values;
+mod rewards;
+mod validator_manager;
+use access_control::AccessControl;
+use call_graph::CallGraph;
+use node_ids::{ValidatorSnapshot, ValidatorSummary};
+use precompile_calls::{CallForm, Method};
+use return_values::SuccessFlag;
 
 #[derive(Serialize, Deserialize, Debug)]
 struct AnalysisJob {
     job_id: String,
     source_code: String,
+    // Network whose staking rules apply, e.g. `fuji`; mainnet when absent.
+    #[serde(default)]
+    target_network: Option<String>,
+    // Staking rules of a Subnet, keyed like `registry/staking_rules.json`. Fields it omits come
+    // from the `target_network` profile.
+    #[serde(default)]
+    staking_rules: Option<Map<String, Value>>,
 }
 
 // V3 FIX: Add traits for HashSet
@@ -23,12 +50,11 @@ struct AnalysisResult {
     job_id: String,
     worker_name: String,
     output: Vec<PrecompileIssue>,
+    // Hard-coded validators, deduplicated.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    validators: Vec<ValidatorSummary>,
 }
 
-const STAKING_PRECOMPILES: &[(&str, &str)] = &[
-    ("0x0100000000000000000000000000000000000000", "P-Chain Handler"),
-];
-
 fn main() -> redis::RedisResult<()> {
     println!("Starting Staking Precompile Worker [V3]...");
 
@@ -36,11 +62,15 @@ fn main() -> redis::RedisResult<()> {
     let mut redis_con = redis_client.get_connection()?;
     println!("Successfully connected to Redis.");
 
-    listen_for_jobs(&mut redis_con);
+    let precompiles = PrecompileRegistry::load().unwrap_or_else(|e| {
+        eprintln!("{}. Falling back to the built-in precompile registry.", e);
+        PrecompileRegistry::builtin()
+    });
+    listen_for_jobs(&mut redis_con, &precompiles);
     Ok(())
 }
 
-fn listen_for_jobs(con: &mut Connection) {
+fn listen_for_jobs(con: &mut Connection, precompiles: &PrecompileRegistry) {
     let channel = "staking_precompile_jobs";
     println!("Listening for jobs on channel: '{}'", channel);
 
@@ -53,10 +83,22 @@ fn listen_for_jobs(con: &mut Connection) {
                 match job {
                     Ok(parsed_job) => {
                         println!("\nProcessing Job ID: {}", parsed_job.job_id);
-                        let result = analyze_staking_precompiles_v3(&parsed_job);
+                        // Re-read for every job so a refreshed export takes effect without a restart.
+                        let snapshot = ValidatorSnapshot::load().unwrap_or_else(|e| {
+                            eprintln!("{}. Continuing without the validator snapshot.", e);
+                            None
+                        });
+                        let result = analyze_staking_precompiles_v3(&parsed_job, precompiles, snapshot.as_ref());
                         publish_result(con, result);
                     }
-                    Err(e) => eprintln!("Error parsing job JSON: {}", e),
+                    Err(e) => {
+                        eprintln!("Error parsing job JSON: {}", e);
+                        // Report the error to the requester when the job can still be identified.
+                        let job_id = serde_json::from_str::<Value>(job_json).ok().and_then(|v| v.get("job_id")?.as_str().map(str::to_string));
+                        if let Some(job_id) = job_id {
+                            publish_result(con, invalid_job_result(job_id, &e.to_string()));
+                        }
+                    }
                 }
             }
             Err(e) => eprintln!("Error receiving job from Redis: {}", e),
@@ -64,79 +106,119 @@ fn listen_for_jobs(con: &mut Connection) {
     }
 }
 
-fn analyze_staking_precompiles_v3(job: &AnalysisJob) -> AnalysisResult {
+fn analyze_staking_precompiles_v3(job: &AnalysisJob, precompiles: &PrecompileRegistry, snapshot: Option<&ValidatorSnapshot>) -> AnalysisResult {
     let mut issues: Vec<PrecompileIssue> = Vec::new();
     let code = &job.source_code;
 
-    let function_regex = Regex::new(r"function\s+([a-zA-Z0-9_]+)\s*\((.*?)\)\s*(public|external|internal|private)\s*(.*?)\s*\{").unwrap();
     let payable_modifier_regex = Regex::new(r"\bpayable\b").unwrap();
-    let low_level_call_regex = Regex::new(r"\.call\b").unwrap();
-    let access_control_regex = Regex::new(r"\b(onlyOwner|onlyRole|require\(msg\.sender\s*==\s*[a-zA-Z0-9_]+\)|_checkRole)\b").unwrap();
-    let reward_withdrawal_regex = Regex::new(r"function\s+(withdraw|claim|distribute|release)Rewards\b").unwrap();
-    let validator_id_regex = Regex::new(r"NodeID-[a-zA-Z0-9]+").unwrap();
-
-    let mut interacts_with_staking = false;
-
-    for (i, line_content) in code.lines().enumerate() {
-        let line_num = (i + 1) as u32;
-
-        for (address, name) in STAKING_PRECOMPILES {
-            let regex_str = format!(r"(?i)\b{}\b", address);
-            let re = Regex::new(&regex_str).unwrap();
-
-            if re.is_match(line_content) {
-                interacts_with_staking = true;
-                
-                issues.push(PrecompileIssue {
-                    line: line_num,
-                    issue_type: "P-Chain Precompile Interaction".to_string(),
-                    description: format!("Direct interaction with the {} precompile detected.", name),
-                    recommendation: "This is a powerful, low-level operation. Review its correctness and security properties. Specific checks below.".to_string(),
-                });
-
-                let mut current_func_start_line = 0;
-                let mut current_func_signature = "";
-                for j in (0..=i).rev() {
-                    if let Some(func_match) = function_regex.captures(code.lines().nth(j).unwrap_or_default()) {
-                        current_func_start_line = (j + 1) as u32;
-                        current_func_signature = func_match.get(0).unwrap().as_str();
-                        break;
-                    }
-                }
 
-                if !payable_modifier_regex.is_match(current_func_signature) {
-                    issues.push(PrecompileIssue { line: current_func_start_line, issue_type: "Missing Payable Modifier".to_string(), description: format!("The function interacting with a staking precompile is not marked `payable`."), recommendation: "Ensure functions that may send AVAX for staking/delegation are marked `payable`.".to_string()});
-                }
+    let masked = mask_comments_and_strings(code);
+    let outline = SourceOutline::parse(code);
+    let calls = precompile_calls::find_precompile_calls(code, &masked, &outline, precompiles);
+    let access = AccessControl::analyze(&masked, &outline);
+    let graph = CallGraph::build(&masked, &outline);
 
-                if low_level_call_regex.is_match(line_content) && !line_content.contains("require(") && !line_content.contains("=") {
-                    issues.push(PrecompileIssue { line: line_num, issue_type: "Unchecked Return Value".to_string(), description: "The return value of a low-level call to a precompile is not checked.".to_string(), recommendation: "Always check the `success` boolean from low-level calls using `require(success, ...)` to prevent silent failures.".to_string()});
-                }
+    for call in &calls {
+        let precompile = call.precompile;
+        let function = outline.function_at_line(call.line);
+        let arguments = if call.arguments.is_empty() { String::new() } else { format!(" with arguments ({})", call.arguments.iter().map(|a| format!("`{}`", a)).collect::<Vec<_>>().join(", ")) };
+        let description = match call.method.called() {
+            _ if call.form == CallForm::Unresolved => format!("`{}` refers to the {} precompile, but no call through it was recognised (e.g. it is called from assembly or through a helper taking the address as a parameter), so the method and arguments could not be checked.", call.expression, precompile.name),
+            (Some(signature), Some(selector)) => format!("`{}` calls `{}` (selector {}) on the {} precompile{}.", call.expression, signature, selector, precompile.name, arguments),
+            (Some(signature), None) => format!("`{}` calls `{}` on the {} precompile{}.", call.expression, signature, precompile.name, arguments),
+            (None, Some(selector)) => format!("`{}` calls selector {} on the {} precompile{}.", call.expression, selector, precompile.name, arguments),
+            (None, None) => format!("`{}` calls the {} precompile with a payload whose selector could not be decoded.", call.expression, precompile.name),
+        };
+        let description = match function.and_then(|f| access.guard(f).map(|g| (f, g))) {
+            Some((f, guard)) => format!("{} `{}` is restricted by {}.", description, f.name, guard.describe()),
+            None => description,
+        };
+        issues.push(PrecompileIssue {
+            line: call.line,
+            issue_type: "P-Chain Precompile Interaction".to_string(),
+            description,
+            recommendation: "This is a powerful, low-level operation. Review its correctness and security properties. Specific checks below.".to_string(),
+        });
 
-                if (current_func_signature.contains("public") || current_func_signature.contains("external")) && !access_control_regex.is_match(current_func_signature) {
-                     issues.push(PrecompileIssue { line: current_func_start_line, issue_type: "Weak Access Control".to_string(), description: "A public/external function interacting with a staking precompile lacks explicit access control.".to_string(), recommendation: "Functions that can alter staking state should be strictly controlled (e.g., `onlyOwner`).".to_string()});
-                }
+        let implemented: Vec<String> = precompile.functions.iter().map(|f| format!("`{}`", f.signature)).collect();
+        match &call.method {
+            Method::Mismatch { function, declared } => issues.push(PrecompileIssue {
+                line: call.line,
+                issue_type: "Precompile Signature Mismatch".to_string(),
+                description: format!("The interface declares `{}` (selector {}), but the {} precompile implements `{}` (selector {}). The call reverts.", declared, call.method.called().1.unwrap_or_default(), precompile.name, function.signature, function.selector()),
+                recommendation: format!("Declare the method with the precompile's exact parameter types: `function {}`.", function.signature),
+            }),
+            Method::Unknown { signature, selector } => issues.push(PrecompileIssue {
+                line: call.line,
+                issue_type: "Unknown Precompile Method".to_string(),
+                description: format!("{} does not match any method of the {} precompile, so the call reverts.", signature.as_ref().or(selector.as_ref()).map(|m| format!("`{}`", m)).unwrap_or_default(), precompile.name),
+                recommendation: format!("Call one of the methods the precompile implements: {}.", implemented.join(", ")),
+            }),
+            Method::Known(_) | Method::Undecoded => {}
+        }
+
+        // Value and the caller's identity come from the public entry points, so both are judged there.
+        let entry_paths = function.map(|f| graph.entry_paths(f, |_| true)).unwrap_or_default();
+        if call.method.may_need_value() {
+            for path in entry_paths.iter().filter(|p| !payable_modifier_regex.is_match(&p[0].header)) {
+                let description = match path.as_slice() {
+                    [entry] => format!("The {} function `{}` interacts with a staking precompile but is not marked `payable`.", visibility(entry), entry.name),
+                    [entry, ..] => format!("The {} function `{}` reaches a staking precompile call through {} but is not marked `payable`.", visibility(entry), entry.name, call_graph::describe_path(path)),
+                    [] => continue,
+                };
+                issues.push(PrecompileIssue { line: path[0].start_line, issue_type: "Missing Payable Modifier".to_string(), description, recommendation: "Ensure functions that may send AVAX for staking/delegation are marked `payable`.".to_string()});
             }
         }
-        
-        if validator_id_regex.is_match(line_content) {
-             issues.push(PrecompileIssue {
-                line: line_num,
-                issue_type: "Hardcoded Validator Dependency".to_string(),
-                description: "A hardcoded validator NodeID was found.".to_string(),
-                recommendation: "This creates a dependency on a single validator. Implement off-chain monitoring for this validator's health (uptime, fees, status) and have a contingency plan if it becomes unreliable or malicious.".to_string(),
-            });
+
+        if call.form == CallForm::LowLevel {
+            let description = match &call.success {
+                SuccessFlag::Checked => None,
+                SuccessFlag::Discarded => Some("The return value of a low-level call to a precompile is not checked.".to_string()),
+                SuccessFlag::Unread(flag) => Some(format!("The success flag of a low-level call to a precompile is stored in `{}`, which is never read.", flag)),
+                SuccessFlag::NotChecked(flag) => Some(format!("The success flag of a low-level call to a precompile is stored in `{}`, which is only logged or stored, never checked.", flag)),
+            };
+            if let Some(description) = description {
+                issues.push(PrecompileIssue { line: call.line, issue_type: "Unchecked Return Value".to_string(), description, recommendation: "Always check the `success` boolean from low-level calls using `require(success, ...)` to prevent silent failures.".to_string()});
+            }
+        }
+
+        // A guard anywhere on the path protects the call, so only unguarded functions are followed.
+        let unguarded_paths = function.map(|f| graph.entry_paths(f, |g| access.guard(g).is_none())).unwrap_or_default();
+        for path in unguarded_paths {
+            let description = match path.as_slice() {
+                [entry] => format!("The {} function `{}` interacts with a staking precompile without access control.", visibility(entry), entry.name),
+                [entry, ..] => format!("The {} function `{}` reaches a staking precompile call through {} without access control on the way.", visibility(entry), entry.name, call_graph::describe_path(&path)),
+                [] => continue,
+            };
+            issues.push(PrecompileIssue { line: path[0].start_line, issue_type: "Weak Access Control".to_string(), description, recommendation: "Functions that can alter staking state should be strictly controlled (e.g., `onlyOwner`, `onlyRole`, or a `msg.sender` check that reverts).".to_string()});
         }
     }
 
-    if interacts_with_staking && !reward_withdrawal_regex.is_match(code) {
-        issues.push(PrecompileIssue {
-            line: 0,
-            issue_type: "Locked Rewards Hazard".to_string(),
-            description: "The contract interacts with staking precompiles but appears to lack a function for withdrawing or distributing staking rewards.".to_string(),
-            recommendation: "Ensure your contract has a clear and secure mechanism (e.g., a `claimRewards()` or `distribute()` function) for users or administrators to access the staking rewards earned by the contract.".to_string(),
-        });
+    match staking_rules(job) {
+        Ok(rules) => bounds::report_bounds(&calls, &masked, &outline, &rules, &mut issues),
+        Err(e) => if !calls.is_empty() {
+            issues.push(PrecompileIssue {
+                line: 0,
+                issue_type: "Staking Rules Unavailable".to_string(),
+                description: format!("Stake amounts, durations and delegation fees were not checked against P-Chain rules: {}.", e),
+                recommendation: "Pass the Subnet's staking rules as `staking_rules`, in the format of `registry/staking_rules.json`, or set `target_network` to a network with a built-in profile (`mainnet` or `fuji`).".to_string(),
+            });
+        },
     }
-    
+
+    lst::report_lst(&masked, &outline, &graph, &mut issues);
+
+    validator_manager::report_validator_manager(&masked, &outline, &graph, &mut issues);
+
+    let validators = node_ids::report_node_ids(code, snapshot, &mut issues);
+
+    lifecycle::report_lifecycle(&calls, &masked, &outline, &mut issues);
+
+    if !calls.is_empty() {
+        let staking_lines: Vec<u32> = calls.iter().map(|c| c.line).collect();
+        rewards::report_rewards(&masked, &outline, &graph, &access, &staking_lines, &mut issues);
+    }
+
     println!("V3 analysis complete. Found {} precompile issues for Job ID: {}", issues.len(), job.job_id);
     
     // --- V3 FIX: Use HashSet for robust deduplication ---
@@ -148,9 +230,42 @@ fn analyze_staking_precompiles_v3(job: &AnalysisJob) -> AnalysisResult {
         job_id: job.job_id.clone(),
         worker_name: "StakingPrecompileWorkerV3".to_string(),
         output,
+        validators,
+    }
+}
+
+/// The job's `staking_rules` over the `target_network` profile (mainnet by default). A network
+/// without a built-in profile has no rules unless the job supplies all of them.
+fn staking_rules(job: &AnalysisJob) -> Result<StakingRules, String> {
+    let profile = match job.target_network.as_deref() {
+        Some(network) => StakingRules::for_network(network),
+        None => Some(StakingRules::mainnet()),
+    };
+    match (&job.staking_rules, profile) {
+        (Some(overrides), profile) => StakingRules::with_overrides(profile.as_ref(), overrides),
+        (None, Some(rules)) => Ok(rules),
+        (None, None) => Err(format!("there is no built-in profile for network '{}' and the job supplies no `staking_rules`", job.target_network.as_deref().unwrap_or_default())),
+    }
+}
+
+fn invalid_job_result(job_id: String, error: &str) -> AnalysisResult {
+    AnalysisResult {
+        job_id,
+        worker_name: "StakingPrecompileWorkerV3".to_string(),
+        output: vec![PrecompileIssue {
+            line: 0,
+            issue_type: "Invalid Job".to_string(),
+            description: format!("The staking analysis could not run because the job is malformed: {}.", error),
+            recommendation: "Fix the job payload and submit it again.".to_string(),
+        }],
+        validators: Vec::new(),
     }
 }
 
+fn visibility(function: &FunctionSpan) -> &'static str {
+    if function.header.contains("external") || function.kind != FunctionKind::Function { "external" } else { "public" }
+}
+
 fn publish_result(con: &mut Connection, result: AnalysisResult) {
     let channel = "sentinel_results";
     match serde_json::to_string(&result) {
@@ -0,0 +1,222 @@
+use std::env;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use sentinel_common::solidity::line_at;
+
+use crate::PrecompileIssue;
+
+pub const SNAPSHOT_ENV_VAR: &str = "SENTINEL_VALIDATOR_SNAPSHOT";
+
+// A NodeID is the CB58 encoding of a 20-byte short ID followed by a 4-byte checksum.
+const NODE_ID_LENGTH: usize = 20;
+const CHECKSUM_LENGTH: usize = 4;
+
+// Validators whose stake ends within this window are reported as ending soon.
+const ENDING_SOON_SECONDS: u64 = 30 * 24 * 60 * 60;
+
+/// A validator from a `platform.getCurrentValidators` export.
+#[derive(Debug, Clone)]
+pub struct SnapshotValidator {
+    pub node_id: String,
+    pub end_time: Option<u64>,
+    // Percentage, as the API returns it, e.g. "2.0000".
+    pub delegation_fee: Option<String>,
+}
+
+pub struct ValidatorSnapshot {
+    pub validators: Vec<SnapshotValidator>,
+}
+
+impl ValidatorSnapshot {
+    /// Reads the snapshot named by `SENTINEL_VALIDATOR_SNAPSHOT`, if set. Accepts the full
+    /// JSON-RPC response or just its `result`.
+    pub fn load() -> Result<Option<Self>, String> {
+        let Ok(path) = env::var(SNAPSHOT_ENV_VAR) else { return Ok(None) };
+        let contents = fs::read_to_string(&path).map_err(|e| format!("Failed to read validator snapshot '{}': {}", path, e))?;
+        let json: Value = serde_json::from_str(&contents).map_err(|e| format!("Invalid validator snapshot '{}': {}", path, e))?;
+        let validators = json.pointer("/result/validators").or_else(|| json.get("validators"))
+            .and_then(Value::as_array)
+            .ok_or_else(|| format!("Invalid validator snapshot '{}': no `validators` array", path))?;
+        let validators = validators.iter()
+            .filter_map(|v| {
+                Some(SnapshotValidator {
+                    node_id: v.get("nodeID")?.as_str()?.to_string(),
+                    // Timestamps are strings in the API, but accept numbers too.
+                    end_time: v.get("endTime").and_then(|t| t.as_u64().or_else(|| t.as_str()?.parse().ok())),
+                    delegation_fee: v.get("delegationFee").and_then(|f| f.as_str().map(str::to_string).or_else(|| f.as_f64().map(|n| n.to_string()))),
+                })
+            })
+            .collect();
+        Ok(Some(ValidatorSnapshot { validators }))
+    }
+
+    fn find(&self, node_id: &str) -> Option<&SnapshotValidator> {
+        self.validators.iter().find(|v| v.node_id == node_id)
+    }
+}
+
+/// A hard-coded NodeID and what is known about it, for the result's validator summary.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ValidatorSummary {
+    pub node_id: String,
+    pub lines: Vec<u32>,
+    pub valid: bool,
+    // `None` when no snapshot was supplied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_snapshot: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegation_fee: Option<String>,
+}
+
+/// Why a NodeID does not decode.
+fn decode_error(node_id: &str) -> Option<String> {
+    let encoded = node_id.trim_start_matches("NodeID-");
+    let bytes = match bs58::decode(encoded).into_vec() {
+        Ok(bytes) => bytes,
+        Err(_) => return Some("contains characters outside the base58 alphabet (`0`, `O`, `I` and `l` are not used)".to_string()),
+    };
+    if bytes.len() != NODE_ID_LENGTH + CHECKSUM_LENGTH {
+        return Some(format!("decodes to {} bytes instead of {} (a 20-byte ID and a 4-byte checksum)", bytes.len(), NODE_ID_LENGTH + CHECKSUM_LENGTH));
+    }
+    let (id, checksum) = bytes.split_at(NODE_ID_LENGTH);
+    let digest = Sha256::digest(id);
+    (checksum != &digest[digest.len() - CHECKSUM_LENGTH..]).then(|| "has an invalid checksum, so it is probably mistyped".to_string())
+}
+
+/// Decodes every hard-coded NodeID, reports malformed ones, and reports each valid one once,
+/// checked against the validator snapshot when one is supplied.
+pub fn report_node_ids(source: &str, snapshot: Option<&ValidatorSnapshot>, issues: &mut Vec<PrecompileIssue>) -> Vec<ValidatorSummary> {
+    let node_id = Regex::new(r"NodeID-[a-zA-Z0-9]+").unwrap();
+    let mut summaries: Vec<ValidatorSummary> = Vec::new();
+    for m in node_id.find_iter(source) {
+        let line = line_at(source, m.start());
+        match summaries.iter_mut().find(|s| s.node_id == m.as_str()) {
+            Some(summary) if !summary.lines.contains(&line) => summary.lines.push(line),
+            Some(_) => {}
+            None => summaries.push(ValidatorSummary { node_id: m.as_str().to_string(), lines: vec![line], valid: true, in_snapshot: None, end_time: None, delegation_fee: None }),
+        }
+    }
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    for summary in &mut summaries {
+        let line = summary.lines[0];
+        let elsewhere = if summary.lines.len() > 1 {
+            format!(" It also appears on line(s) {}.", summary.lines[1..].iter().map(u32::to_string).collect::<Vec<_>>().join(", "))
+        } else {
+            String::new()
+        };
+        if let Some(reason) = decode_error(&summary.node_id) {
+            summary.valid = false;
+            issues.push(PrecompileIssue {
+                line,
+                issue_type: "Malformed NodeID".to_string(),
+                description: format!("`{}` is not a valid NodeID: it {}.{} Staking calls using it revert.", summary.node_id, reason, elsewhere),
+                recommendation: "Copy the NodeID from `info.getNodeID` or `platform.getCurrentValidators` on the node it belongs to.".to_string(),
+            });
+            continue;
+        }
+
+        let validator = snapshot.map(|s| s.find(&summary.node_id));
+        let details = match validator.flatten() {
+            Some(v) => {
+                summary.end_time = v.end_time;
+                summary.delegation_fee = v.delegation_fee.clone();
+                let fee = v.delegation_fee.as_deref().map(|f| format!(", delegation fee {}%", f)).unwrap_or_default();
+                let end = v.end_time.map(|t| format!(", stake ends at timestamp {}", t)).unwrap_or_default();
+                format!(" The snapshot lists it as a current validator{}{}.", end, fee)
+            }
+            None => String::new(),
+        };
+        issues.push(PrecompileIssue {
+            line,
+            issue_type: "Hardcoded Validator Dependency".to_string(),
+            description: format!("A hardcoded validator NodeID was found: `{}`.{}{}", summary.node_id, elsewhere, details),
+            recommendation: "This creates a dependency on a single validator. Implement off-chain monitoring for this validator's health (uptime, fees, status) and have a contingency plan if it becomes unreliable or malicious.".to_string(),
+        });
+
+        let Some(validator) = validator else { continue };
+        summary.in_snapshot = Some(validator.is_some());
+        match validator {
+            None => issues.push(PrecompileIssue {
+                line,
+                issue_type: "Validator Not In Current Set".to_string(),
+                description: format!("`{}` is not in the supplied validator set snapshot, so delegating to it fails.", summary.node_id),
+                recommendation: "Check whether the validator stopped validating or the ID belongs to another network, and make the validator configurable instead of hard-coded.".to_string(),
+            }),
+            Some(v) => if let Some(end) = v.end_time.filter(|t| *t < now + ENDING_SOON_SECONDS) {
+                issues.push(PrecompileIssue {
+                    line,
+                    issue_type: "Validator Stake Ending".to_string(),
+                    description: if end <= now {
+                        format!("The stake of `{}` ended at timestamp {}, so new delegations to it fail.", summary.node_id, end)
+                    } else {
+                        format!("The stake of `{}` ends at timestamp {}, within 30 days. Delegations must end before the validator's stake does.", summary.node_id, end)
+                    },
+                    recommendation: "Make the validator configurable so delegations can move to another one, and cap delegation periods at the validator's end time.".to_string(),
+                });
+            },
+        }
+    }
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(id: &[u8; NODE_ID_LENGTH]) -> String {
+        let digest = Sha256::digest(id);
+        let bytes: Vec<u8> = id.iter().chain(&digest[digest.len() - CHECKSUM_LENGTH..]).copied().collect();
+        format!("NodeID-{}", bs58::encode(bytes).into_string())
+    }
+
+    #[test]
+    fn encoded_ids_round_trip() {
+        for id in [[0u8; NODE_ID_LENGTH], [0xff; NODE_ID_LENGTH], core::array::from_fn(|i| i as u8)] {
+            let node_id = encode(&id);
+            assert_eq!(decode_error(&node_id), None, "{}", node_id);
+            let decoded = bs58::decode(node_id.trim_start_matches("NodeID-")).into_vec().unwrap();
+            assert_eq!(&decoded[..NODE_ID_LENGTH], &id);
+        }
+    }
+
+    #[test]
+    fn accepts_published_node_id() {
+        assert_eq!(decode_error("NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg"), None);
+    }
+
+    #[test]
+    fn rejects_bad_checksum() {
+        let mut bytes = bs58::decode("7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg").into_vec().unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let node_id = format!("NodeID-{}", bs58::encode(bytes).into_string());
+        assert!(decode_error(&node_id).unwrap().contains("checksum"));
+    }
+
+    #[test]
+    fn rejects_wrong_length_and_alphabet() {
+        let short = format!("NodeID-{}", bs58::encode([1u8; NODE_ID_LENGTH]).into_string());
+        assert!(decode_error(&short).unwrap().contains("20 bytes instead of 24"));
+        assert!(decode_error("NodeID-0Xhw2mDxuDS44j42TCB6U5579esbSt3Lg").unwrap().contains("base58"));
+    }
+
+    #[test]
+    fn reports_each_node_id_once() {
+        let source = "string a = \"NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg\";\nstring b = \"NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg\";\n";
+        let mut issues = Vec::new();
+        let summaries = report_node_ids(source, None, &mut issues);
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].lines, vec![1, 2]);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].issue_type, "Hardcoded Validator Dependency");
+    }
+}
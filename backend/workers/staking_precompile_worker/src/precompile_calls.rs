@@ -0,0 +1,348 @@
+use std::ops::Range;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use sentinel_common::address_bindings::AddressBindings;
+use sentinel_common::address_literals;
+use sentinel_common::precompiles::{Precompile, PrecompileFunction, PrecompileRegistry};
+use sentinel_common::solidity::{matching_close, split_arguments, SourceOutline};
+
+use crate::return_values::{self, SuccessFlag};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallForm {
+    /// `IStaking(STAKING).addDelegator(...)`.
+    Interface,
+    /// `STAKING.call(abi.encodeWithSelector(...))` and friends.
+    LowLevel,
+    /// The address is written in the source, but no call through it was recognised.
+    Unresolved,
+}
+
+#[derive(Debug)]
+pub enum Method<'a> {
+    /// A function the precompile registry lists for this precompile.
+    Known(&'a PrecompileFunction),
+    /// Named like a registry function, but declared with parameter types that give another selector.
+    Mismatch { function: &'a PrecompileFunction, declared: String },
+    /// A signature or selector the precompile does not implement.
+    Unknown { signature: Option<String>, selector: Option<String> },
+    /// The payload could not be traced back to a selector.
+    Undecoded,
+}
+
+impl Method<'_> {
+    /// Signature and selector of what is actually called, as far as they are known.
+    pub fn called(&self) -> (Option<String>, Option<String>) {
+        match self {
+            Method::Known(function) => (Some(function.signature.clone()), Some(function.selector())),
+            Method::Mismatch { declared, .. } => (Some(declared.clone()), Some(selector_of(declared))),
+            Method::Unknown { signature, selector } => (signature.clone(), selector.clone()),
+            Method::Undecoded => (None, None),
+        }
+    }
+
+    /// Whether the call may need to send value: payable methods, and calls that could not be matched.
+    pub fn may_need_value(&self) -> bool {
+        match self {
+            Method::Known(function) | Method::Mismatch { function, .. } => function.payable,
+            Method::Unknown { .. } | Method::Undecoded => true,
+        }
+    }
+}
+
+/// A call to a staking precompile, resolved through literals, constants, immutables and
+/// locals holding its address.
+#[derive(Debug)]
+pub struct PrecompileCall<'a> {
+    pub line: u32,
+    pub precompile: &'a Precompile,
+    pub form: CallForm,
+    pub method: Method<'a>,
+    /// Receiver and method as written, e.g. `IStaking(STAKING).addDelegator`.
+    pub expression: String,
+    pub arguments: Vec<String>,
+    /// The `value` call option, e.g. `msg.value` in `STAKING.call{value: msg.value}(data)`.
+    pub value: Option<String>,
+    /// What happens to the success flag of a low-level call.
+    pub success: SuccessFlag,
+}
+
+static ENCODER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^abi\s*\.\s*(encodeWithSelector|encodeWithSignature|encodeCall)\s*\(").unwrap());
+static IDENTIFIER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[A-Za-z_$][\w$]*$").unwrap());
+static SELECTOR_MEMBER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\.\s*([A-Za-z_$][\w$]*)\s*\.\s*selector$").unwrap());
+static HASHED_SIGNATURE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^bytes4\s*\(\s*keccak256\s*\(").unwrap());
+static SELECTOR_LITERAL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^0x[0-9a-fA-F]{8}$").unwrap());
+static ASSIGNMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b([A-Za-z_$][\w$]*)\s*=[^=>]").unwrap());
+static FUNCTION_DECLARATION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\bfunction\s+([A-Za-z_$][\w$]*)\s*\(").unwrap());
+static STRING_LITERAL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#""([^"]*)"|'([^']*)'"#).unwrap());
+
+// What a payload or call names, before it is matched against the registry.
+enum MethodRef {
+    Signature(String),
+    Selector(String),
+    Name(String, usize),
+}
+
+struct Scanner<'s> {
+    source: &'s str,
+    masked: &'s str,
+    bindings: AddressBindings,
+}
+
+pub fn find_precompile_calls<'r>(source: &str, masked: &str, outline: &SourceOutline, precompiles: &'r PrecompileRegistry) -> Vec<PrecompileCall<'r>> {
+    let scanner = Scanner { source, masked, bindings: AddressBindings::collect(masked, outline) };
+    let mut calls = Vec::new();
+    for call in scanner.bindings.calls(masked) {
+        let Some(precompile) = precompiles.staking().find(|p| p.address.eq_ignore_ascii_case(&call.address)) else { continue };
+        let name = &masked[call.method.clone()];
+        let args = split_arguments(masked, call.arguments.clone());
+        let (form, method, arguments) = match name {
+            "call" | "staticcall" | "delegatecall" => {
+                let (reference, arguments) = args.first().map(|payload| scanner.decode_payload(payload.clone(), call.scope, 0)).unwrap_or((None, Vec::new()));
+                (CallForm::LowLevel, scanner.match_method(precompile, reference), arguments)
+            }
+            _ => (CallForm::Interface, scanner.match_method(precompile, Some(MethodRef::Name(name.to_string(), args.len()))), args),
+        };
+        calls.push(PrecompileCall {
+            line: call.line,
+            precompile,
+            form,
+            method,
+            expression: collapse(&source[call.receiver.start..call.method.end]),
+            arguments: arguments.into_iter().map(|a| collapse(&source[a])).collect(),
+            value: call.options.clone().and_then(|options| call_option(source, masked, options, "value")),
+            success: return_values::success_flag(masked, outline, call.receiver.start),
+        });
+    }
+
+    // A precompile address with no recognised call (called from assembly, or through a helper
+    // taking the address as a parameter) is still reported, where it is written.
+    for literal in address_literals::extract_address_literals(source) {
+        let Some(precompile) = precompiles.staking().find(|p| p.address.eq_ignore_ascii_case(&literal.address)) else { continue };
+        if calls.iter().any(|c| std::ptr::eq(c.precompile, precompile)) {
+            continue;
+        }
+        let Some(occurrence) = literal.occurrences.first() else { continue };
+        calls.push(PrecompileCall {
+            line: occurrence.line,
+            precompile,
+            form: CallForm::Unresolved,
+            method: Method::Undecoded,
+            expression: collapse(&occurrence.context),
+            arguments: Vec::new(),
+            value: None,
+            success: SuccessFlag::Checked,
+        });
+    }
+    calls
+}
+
+impl Scanner<'_> {
+    /// The method a low-level call payload selects, and the ranges of its arguments.
+    fn decode_payload(&self, payload: Range<usize>, scope: Option<usize>, depth: usize) -> (Option<MethodRef>, Vec<Range<usize>>) {
+        let slice = &self.masked[payload.clone()];
+        let start = payload.start + (slice.len() - slice.trim_start().len());
+        let text = slice.trim();
+
+        if let Some(c) = ENCODER.captures(text) {
+            let open = start + c.get(0).unwrap().end() - 1;
+            let Some(close) = matching_close(self.masked, open) else { return (None, Vec::new()) };
+            let parts = split_arguments(self.masked, open + 1..close);
+            let Some(first) = parts.first().cloned() else { return (None, Vec::new()) };
+            return match &c[1] {
+                "encodeWithSelector" => (self.decode_selector(first, 0), parts[1..].to_vec()),
+                "encodeWithSignature" => (string_literal(&self.source[first]).map(|s| MethodRef::Signature(normalize_signature(&s))), parts[1..].to_vec()),
+                _ => {
+                    // `abi.encodeCall(IStaking.addDelegator, (nodeId, amount))`.
+                    let arguments = parts.get(1).map(|tuple| {
+                        let inner = self.masked[tuple.clone()].trim();
+                        let open = tuple.start + self.masked[tuple.clone()].find('(').unwrap_or(0);
+                        if inner.starts_with('(') { split_arguments(self.masked, open + 1..matching_close(self.masked, open).unwrap_or(tuple.end)) } else { vec![tuple.clone()] }
+                    }).unwrap_or_default();
+                    let name = self.masked[first].trim().rsplit('.').next().unwrap_or_default().trim().to_string();
+                    let arity = arguments.len();
+                    (Some(MethodRef::Name(name, arity)), arguments)
+                }
+            };
+        }
+
+        // A payload built earlier: `bytes memory data = abi.encodeWithSelector(...)`.
+        let name = text;
+        if depth < 2 && IDENTIFIER.is_match(name) {
+            if let Some(value) = self.assigned_value(name, scope, payload.start) {
+                return self.decode_payload(value, scope, depth + 1);
+            }
+        }
+        (None, Vec::new())
+    }
+
+    fn decode_selector(&self, expr: Range<usize>, depth: usize) -> Option<MethodRef> {
+        let text = self.masked[expr.clone()].trim();
+        if let Some(c) = SELECTOR_MEMBER.captures(text) {
+            return Some(MethodRef::Name(c[1].to_string(), usize::MAX));
+        }
+        if HASHED_SIGNATURE.is_match(text) {
+            return string_literal(&self.source[expr]).map(|s| MethodRef::Signature(normalize_signature(&s)));
+        }
+        if let Some(m) = SELECTOR_LITERAL.find(text) {
+            return Some(MethodRef::Selector(m.as_str().to_lowercase()));
+        }
+        if depth < 2 && IDENTIFIER.is_match(text) {
+            return self.assigned_value(text, None, expr.start).and_then(|value| self.decode_selector(value, depth + 1));
+        }
+        None
+    }
+
+    /// The value last assigned to `name` before `before`, within the scope or at contract level.
+    fn assigned_value(&self, name: &str, scope: Option<usize>, before: usize) -> Option<Range<usize>> {
+        ASSIGNMENT.captures_iter(self.masked)
+            .filter(|c| &c[1] == name)
+            .map(|c| c.get(0).unwrap())
+            .filter(|m| m.start() < before || self.bindings.scope_at(m.start()).is_none())
+            .filter(|m| self.bindings.scope_at(m.start()).is_none_or(|s| Some(s) == scope))
+            .last()
+            .map(|m| {
+                let value_start = m.end() - 1;
+                let value_end = self.masked[value_start..].find(';').map(|i| value_start + i).unwrap_or(self.masked.len());
+                value_start..value_end
+            })
+    }
+
+    fn match_method<'r>(&self, precompile: &'r Precompile, reference: Option<MethodRef>) -> Method<'r> {
+        let named = |name: &str| precompile.functions.iter().find(|f| function_name(&f.signature) == name);
+        let reference = match reference {
+            // A method named through an interface takes the parameter types the interface declares.
+            Some(MethodRef::Name(name, arity)) => match self.declared_signature(&name) {
+                Some(signature) => MethodRef::Signature(signature),
+                None => MethodRef::Name(name, arity),
+            },
+            Some(reference) => reference,
+            None => return Method::Undecoded,
+        };
+        match reference {
+            MethodRef::Signature(signature) => match precompile.functions.iter().find(|f| f.signature == signature) {
+                Some(function) => Method::Known(function),
+                None => match named(function_name(&signature)) {
+                    Some(function) => Method::Mismatch { function, declared: signature },
+                    None => Method::Unknown { selector: Some(selector_of(&signature)), signature: Some(signature) },
+                },
+            },
+            MethodRef::Selector(selector) => match precompile.functions.iter().find(|f| f.selector() == selector) {
+                Some(function) => Method::Known(function),
+                None => Method::Unknown { signature: None, selector: Some(selector) },
+            },
+            MethodRef::Name(name, arity) => {
+                let candidates: Vec<&PrecompileFunction> = precompile.functions.iter().filter(|f| function_name(&f.signature) == name).collect();
+                // A name with no overload taking this many arguments selects nothing the precompile implements.
+                match candidates.iter().find(|f| arity == usize::MAX || parameter_count(&f.signature) == arity) {
+                    Some(function) => Method::Known(function),
+                    None => Method::Unknown { signature: Some(format!("{}(...)", name)), selector: None },
+                }
+            }
+        }
+    }
+
+    /// The canonical signature of a bodiless `function name(...)` declaration (an interface
+    /// method), if the source has exactly one.
+    fn declared_signature(&self, name: &str) -> Option<String> {
+        let declarations: Vec<String> = FUNCTION_DECLARATION.captures_iter(self.masked)
+            .filter(|c| &c[1] == name)
+            .filter_map(|c| {
+                let m = c.get(0).unwrap();
+                let open = m.end() - 1;
+                let close = matching_close(self.masked, open)?;
+                let rest = &self.masked[close + 1..];
+                let terminator = rest.find([';', '{'])?;
+                if rest.as_bytes()[terminator] != b';' {
+                    return None;
+                }
+                let types: Option<Vec<String>> = split_arguments(self.masked, open + 1..close).into_iter().map(|p| canonical_type(&self.masked[p])).collect();
+                Some(format!("{}({})", name, types?.join(",")))
+            })
+            .collect();
+        match declarations.as_slice() {
+            [signature] => Some(signature.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// The ABI type of a parameter declaration such as `string calldata nodeId`.
+fn canonical_type(parameter: &str) -> Option<String> {
+    let type_name = parameter.split_whitespace().next()?;
+    let (base, array) = type_name.find('[').map(|i| type_name.split_at(i)).unwrap_or((type_name, ""));
+    let base = match base {
+        "uint" => "uint256",
+        "int" => "int256",
+        "byte" => "bytes1",
+        b if b.starts_with(|c: char| c.is_ascii_lowercase()) => b,
+        // Structs and enums do not map to a single ABI type name.
+        _ => return None,
+    };
+    Some(format!("{}{}", base, array))
+}
+
+fn function_name(signature: &str) -> &str {
+    signature.split('(').next().unwrap_or_default()
+}
+
+fn parameter_count(signature: &str) -> usize {
+    let params = signature.split_once('(').map(|(_, p)| p.trim_end_matches(')')).unwrap_or_default();
+    if params.is_empty() { 0 } else { params.split(',').count() }
+}
+
+fn normalize_signature(signature: &str) -> String {
+    signature.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+fn selector_of(signature: &str) -> String {
+    PrecompileFunction { signature: signature.to_string(), required_role: None, payable: false, parameters: Vec::new(), value: None }.selector()
+}
+
+fn string_literal(text: &str) -> Option<String> {
+    STRING_LITERAL.captures(text).and_then(|c| c.get(1).or(c.get(2))).map(|m| m.as_str().to_string())
+}
+
+/// The expression given for one option in `{value: v, gas: g}`.
+fn call_option(source: &str, masked: &str, options: Range<usize>, name: &str) -> Option<String> {
+    split_arguments(masked, options).into_iter().find_map(|option| {
+        let (key, _) = masked[option.clone()].split_once(':')?;
+        let colon = option.start + key.len();
+        (key.trim() == name).then(|| collapse(&source[colon + 1..option.end]))
+    })
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sentinel_common::solidity::mask_comments_and_strings;
+
+    fn methods(source: &str) -> Vec<(Option<String>, Option<String>)> {
+        let registry = PrecompileRegistry::builtin();
+        let masked = mask_comments_and_strings(source);
+        find_precompile_calls(source, &masked, &SourceOutline::parse(source), &registry).iter().map(|c| c.method.called()).collect()
+    }
+
+    #[test]
+    fn interface_call_is_matched_by_name_and_arity() {
+        let source = "contract A {\n    address constant STAKING = 0x0100000000000000000000000000000000000000;\n    function f(string memory id) external payable {\n        IStaking(STAKING).addDelegator{value: msg.value}(id, 14 days);\n        IStaking(STAKING).addDelegator(id, 14 days, 2);\n    }\n}\n";
+        let found = methods(source);
+        assert_eq!(found[0].0.as_deref(), Some("addDelegator(string,uint64)"));
+        assert_eq!(found[1], (Some("addDelegator(...)".to_string()), None));
+    }
+
+    #[test]
+    fn low_level_payloads_are_decoded() {
+        let source = "contract A {\n    address constant STAKING = 0x0100000000000000000000000000000000000000;\n    bytes4 constant ADD = bytes4(keccak256(\"addValidator(string,uint64,uint32)\"));\n    function f(string memory id) external payable {\n        bytes memory data = abi.encodeWithSignature(\"addDelegator(string, uint64)\", id, 14 days);\n        (bool ok,) = STAKING.call{value: msg.value}(data);\n        require(ok);\n        (ok,) = STAKING.call(abi.encodeWithSelector(ADD, id, 14 days, 200));\n        require(ok);\n        (ok,) = STAKING.call(abi.encodeWithSelector(0x12345678));\n        require(ok);\n    }\n}\n";
+        let found = methods(source);
+        assert_eq!(found[0].0.as_deref(), Some("addDelegator(string,uint64)"));
+        assert_eq!(found[1].0.as_deref(), Some("addValidator(string,uint64,uint32)"));
+        assert_eq!(found[2], (None, Some("0x12345678".to_string())));
+    }
+}
@@ -0,0 +1,133 @@
+use regex::Regex;
+
+use sentinel_common::solidity::SourceOutline;
+
+/// What happens to the success flag a low-level call returns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SuccessFlag {
+    /// Tested in a `require`/`assert`/`if`/`while` condition or ternary, returned, or handed to a helper.
+    Checked,
+    /// Not captured at all, e.g. `x.call(data);` or `(, bytes memory ret) = x.call(data);`.
+    Discarded,
+    /// Captured in the named variable, which is never read.
+    Unread(String),
+    /// Captured in the named variable, which is only logged or stored.
+    NotChecked(String),
+}
+
+/// Follows the success flag of the low-level call starting at `call_start` through the rest of
+/// its function, including through locals it is copied into.
+pub fn success_flag(masked: &str, outline: &SourceOutline, call_start: usize) -> SuccessFlag {
+    let body = outline.functions.iter().filter(|f| f.body.contains(&call_start)).min_by_key(|f| f.body.len()).map(|f| f.body.clone()).unwrap_or(0..masked.len());
+    let prefix = masked[statement_start(masked, body.start, call_start)..call_start].trim();
+
+    let Some(lhs) = assignment_target(prefix) else {
+        return if is_condition(prefix) || unclosed_parens(prefix) { SuccessFlag::Checked } else { SuccessFlag::Discarded };
+    };
+    // `(bool ok, bytes memory data) = ...` captures the flag in the first tuple element.
+    let first = lhs.trim().trim_start_matches('(').split(',').next().unwrap_or_default().trim();
+    let Some(name) = first.split_whitespace().last().filter(|n| is_identifier(n)) else { return SuccessFlag::Discarded };
+
+    let statement_end = masked[call_start..body.end].find(';').map(|i| call_start + i + 1).unwrap_or(body.end);
+    let mut names = vec![name.to_string()];
+    let mut read = false;
+    let mut i = 0;
+    while i < names.len() {
+        let pattern = Regex::new(&format!(r"\b{}\b", regex::escape(&names[i]))).unwrap();
+        for m in pattern.find_iter(&masked[statement_end..body.end]) {
+            let at = statement_end + m.start();
+            let context_start = statement_start(masked, body.start, at);
+            let prefix = masked[context_start..at].trim();
+            let rest = &masked[at + m.len()..body.end];
+            let rest = &rest[..rest.find([';', '{']).unwrap_or(rest.len())];
+            if rest.trim_start().starts_with('=') && !rest.trim_start().starts_with("==") {
+                // Reassigned rather than read.
+                continue;
+            }
+            read = true;
+            if is_condition(prefix) || rest.contains('?') {
+                return SuccessFlag::Checked;
+            }
+            if prefix.starts_with("emit") {
+                continue;
+            }
+            match assignment_target(prefix) {
+                Some(target) => {
+                    // Copied into another local: follow it. Stores to state are not checks.
+                    let alias = target.split_whitespace().last().unwrap_or_default();
+                    let declared_here = target.split_whitespace().count() > 1;
+                    if is_identifier(alias) && (declared_here || outline.state_variable(alias).is_none()) && !names.iter().any(|n| n == alias) {
+                        names.push(alias.to_string());
+                    }
+                }
+                None if unclosed_parens(prefix) => return SuccessFlag::Checked,
+                None => {}
+            }
+        }
+        i += 1;
+    }
+    if read { SuccessFlag::NotChecked(name.to_string()) } else { SuccessFlag::Unread(name.to_string()) }
+}
+
+fn statement_start(masked: &str, floor: usize, at: usize) -> usize {
+    masked[floor..at].rfind([';', '{', '}']).map(|i| floor + i + 1).unwrap_or(floor)
+}
+
+/// The left-hand side when `prefix` ends in a plain assignment (`=`, not `==`, `<=`, ...).
+fn assignment_target(prefix: &str) -> Option<&str> {
+    let bytes = prefix.as_bytes();
+    let assign = (0..bytes.len()).rev().find(|&i| {
+        bytes[i] == b'='
+            && bytes.get(i + 1).is_none_or(|n| *n != b'=' && *n != b'>')
+            && (i == 0 || !matches!(bytes[i - 1], b'=' | b'!' | b'<' | b'>'))
+    })?;
+    // An `=` inside an open call, e.g. `foo(x = ...`, is not an assignment statement.
+    let lhs = &prefix[..assign];
+    (!unclosed_parens(lhs)).then_some(lhs)
+}
+
+fn is_condition(prefix: &str) -> bool {
+    Regex::new(r"^(require|assert|if|while|return|else\s+if)\b").unwrap().is_match(prefix)
+}
+
+/// Whether `prefix` leaves a parenthesis open, i.e. the expression is a call argument.
+fn unclosed_parens(prefix: &str) -> bool {
+    prefix.matches('(').count() > prefix.matches(')').count()
+}
+
+fn is_identifier(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$') && !text.starts_with(|c: char| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sentinel_common::solidity::mask_comments_and_strings;
+
+    fn flag(body: &str) -> SuccessFlag {
+        let source = format!("contract A {{\n    bool lastOk;\n    event Called(bool ok);\n    error Failed();\n    function f(address target, bytes memory data) external returns (bool) {{\n        {}\n    }}\n}}\n", body);
+        let masked = mask_comments_and_strings(&source);
+        let call_start = masked.find("target.call").unwrap();
+        success_flag(&masked, &SourceOutline::parse(&source), call_start)
+    }
+
+    #[test]
+    fn unread_and_discarded_flags() {
+        assert_eq!(flag("(bool ok, ) = target.call(data);"), SuccessFlag::Unread("ok".to_string()));
+        assert_eq!(flag("target.call(data);"), SuccessFlag::Discarded);
+        assert_eq!(flag("(, bytes memory ret) = target.call(data);"), SuccessFlag::Discarded);
+    }
+
+    #[test]
+    fn checked_flags() {
+        assert_eq!(flag("(bool ok, ) = target.call(data);\n        if (!ok) revert Failed();"), SuccessFlag::Checked);
+        assert_eq!(flag("(bool ok, ) = target.call(data);\n        return ok;"), SuccessFlag::Checked);
+        assert_eq!(flag("(bool ok, ) = target.call(data);\n        bool copied = ok;\n        require(copied, \"call failed\");"), SuccessFlag::Checked);
+    }
+
+    #[test]
+    fn logged_or_stored_flags_are_not_checks() {
+        assert_eq!(flag("(bool ok, ) = target.call(data);\n        emit Called(ok);"), SuccessFlag::NotChecked("ok".to_string()));
+        assert_eq!(flag("(bool ok, ) = target.call(data);\n        lastOk = ok;"), SuccessFlag::NotChecked("ok".to_string()));
+    }
+}
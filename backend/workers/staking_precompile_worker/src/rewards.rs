@@ -0,0 +1,142 @@
+use regex::Regex;
+
+use sentinel_common::solidity::{line_at, matching_close, split_arguments, FunctionKind, FunctionSpan, SourceOutline};
+
+use crate::access_control::AccessControl;
+use crate::call_graph::CallGraph;
+use crate::PrecompileIssue;
+
+/// A transfer of value out of the contract.
+struct Transfer {
+    offset: usize,
+    // "AVAX" or "tokens".
+    asset: &'static str,
+}
+
+/// Checks that staking rewards can actually leave the contract, through a path the stakers can
+/// use, and that the accounting around the transfers is ordered and rounded safely.
+/// Value sent to the precompiles themselves (on `staking_lines`) is staked, not paid out.
+pub fn report_rewards(masked: &str, outline: &SourceOutline, graph: &CallGraph, access: &AccessControl, staking_lines: &[u32], issues: &mut Vec<PrecompileIssue>) {
+    let transfers = |function: &FunctionSpan| -> Vec<Transfer> { transfers(masked, function).into_iter().filter(|t| !staking_lines.contains(&line_at(masked, t.offset))).collect() };
+    let reward_name = Regex::new(r"(?i)reward|claim|harvest|distribut").unwrap();
+    let reward_functions: Vec<&FunctionSpan> = outline.functions.iter().filter(|f| f.kind == FunctionKind::Function && reward_name.is_match(&f.name)).collect();
+
+    // (entry point, reward function) pairs through which rewards really leave the contract.
+    let mut working: Vec<(&FunctionSpan, &FunctionSpan)> = Vec::new();
+    for &reward in &reward_functions {
+        let transfers: usize = graph.reachable_from(reward).iter().map(|f| transfers(f).len()).sum();
+        if transfers == 0 {
+            issues.push(PrecompileIssue {
+                line: reward.start_line,
+                issue_type: "Reward Withdrawal Without Transfer".to_string(),
+                description: format!("`{}` looks like a reward withdrawal, but neither it nor the functions it calls transfer AVAX or tokens out of the contract.", reward.name),
+                recommendation: "Make sure the reward path ends in a transfer to the staker (e.g. `Address.sendValue`, a checked `call{value: ...}` or `SafeERC20.safeTransfer`), not only in bookkeeping.".to_string(),
+            });
+            continue;
+        }
+        let entries = graph.entry_paths(reward, |_| true);
+        if entries.is_empty() {
+            issues.push(PrecompileIssue {
+                line: reward.start_line,
+                issue_type: "Unreachable Reward Withdrawal".to_string(),
+                description: format!("`{}` transfers rewards out, but no public or external function reaches it.", reward.name),
+                recommendation: "Expose the reward withdrawal through an external function the intended party can call.".to_string(),
+            });
+        }
+        working.extend(entries.iter().map(|path| (path[0], reward)));
+    }
+
+    if working.is_empty() {
+        let description = if reward_functions.is_empty() {
+            "The contract interacts with staking precompiles but appears to lack a function for withdrawing or distributing staking rewards.".to_string()
+        } else {
+            "The contract interacts with staking precompiles, but none of its reward functions both transfers value out and is reachable from a public or external function.".to_string()
+        };
+        issues.push(PrecompileIssue {
+            line: 0,
+            issue_type: "Locked Rewards Hazard".to_string(),
+            description,
+            recommendation: "Ensure your contract has a clear and secure mechanism (e.g., a `claimRewards()` or `distribute()` function) for users or administrators to access the staking rewards earned by the contract.".to_string(),
+        });
+    } else if let Some(guard) = working.iter().map(|(entry, _)| access.guard(entry)).collect::<Option<Vec<_>>>() {
+        // Every way out is restricted: stakers depend on a privileged account to receive rewards.
+        let (entry, reward) = working[0];
+        let reaching = if entry.body == reward.body { String::new() } else { format!(" (reaching `{}`)", reward.name) };
+        issues.push(PrecompileIssue {
+            line: entry.start_line,
+            issue_type: "Restricted Reward Withdrawal".to_string(),
+            description: format!("Rewards only leave the contract through restricted functions, e.g. `{}`{}, which is restricted by {}.", entry.name, reaching, guard[0].describe()),
+            recommendation: "If stakers are meant to claim their own rewards, add a permissionless claim that pays `msg.sender` their accrued share; otherwise document and monitor the privileged distribution.".to_string(),
+        });
+    }
+
+    report_accounting_order(masked, outline, &transfers, issues);
+    report_rounding(masked, outline, issues);
+}
+
+/// Flags state updated after value was transferred out in the same function: the recipient can
+/// re-enter while shares or totals are stale.
+fn report_accounting_order(masked: &str, outline: &SourceOutline, transfers: &dyn Fn(&FunctionSpan) -> Vec<Transfer>, issues: &mut Vec<PrecompileIssue>) {
+    for function in outline.functions.iter().filter(|f| f.kind == FunctionKind::Function && !f.header.contains("nonReentrant")) {
+        let Some(first) = transfers(function).into_iter().next() else { continue };
+        let Some((variable, offset)) = state_writes(masked, outline, function).into_iter().find(|(_, offset)| *offset > first.offset) else { continue };
+        issues.push(PrecompileIssue {
+            line: line_at(masked, offset),
+            issue_type: "Accounting After Transfer".to_string(),
+            description: format!("`{}` updates `{}` after transferring {} out (line {}), so the recipient can re-enter while the accounting is stale.", function.name, variable, first.asset, line_at(masked, first.offset)),
+            recommendation: "Update per-user shares and totals before transferring (checks-effects-interactions), or add a `nonReentrant` guard.".to_string(),
+        });
+    }
+}
+
+/// Flags `a / b * c` in share and reward arithmetic, which truncates before scaling.
+fn report_rounding(masked: &str, outline: &SourceOutline, issues: &mut Vec<PrecompileIssue>) {
+    let topic = Regex::new(r"(?i)reward|share|stake|rate|exchange").unwrap();
+    let operand = r"[\w$.]+(?:\s*\[[^\]]*\])*(?:\s*\([^()]*\))?";
+    let divide_then_multiply = Regex::new(&format!(r"{operand}\s*/\s*{operand}\s*\*\s*{operand}")).unwrap();
+    for function in outline.functions.iter().filter(|f| topic.is_match(&masked[f.body.clone()]) || topic.is_match(&f.name)) {
+        for m in divide_then_multiply.find_iter(&masked[function.body.clone()]) {
+            let expression = m.as_str().split_whitespace().collect::<Vec<_>>().join(" ");
+            issues.push(PrecompileIssue {
+                line: line_at(masked, function.body.start + m.start()),
+                issue_type: "Reward Rounding".to_string(),
+                description: format!("`{}` in `{}` divides before multiplying, so the truncation is scaled up and small stakes can round to zero.", expression, function.name),
+                recommendation: "Multiply before dividing (or use `Math.mulDiv`), and round in the protocol's favour: down when paying out or minting shares, up when burning them.".to_string(),
+            });
+        }
+    }
+}
+
+/// Transfers of AVAX or ERC20 tokens in the function body, in source order.
+fn transfers(masked: &str, function: &FunctionSpan) -> Vec<Transfer> {
+    let body = function.body.clone();
+    let member = Regex::new(r"\.\s*(transfer|send|safeTransfer|sendValue)\s*\(|\.\s*call\s*\{\s*value\s*:").unwrap();
+    let mut found = Vec::new();
+    for c in member.captures_iter(&masked[body.clone()]) {
+        let offset = body.start + c.get(0).unwrap().start();
+        let asset = match c.get(1).map(|m| m.as_str()) {
+            None | Some("send") | Some("sendValue") => "AVAX",
+            Some("safeTransfer") => "tokens",
+            // `payable(to).transfer(amount)` sends AVAX, `token.transfer(to, amount)` tokens.
+            _ => {
+                let open = body.start + c.get(0).unwrap().end() - 1;
+                let close = matching_close(masked, open).unwrap_or(open);
+                if split_arguments(masked, open + 1..close).len() > 1 { "tokens" } else { "AVAX" }
+            }
+        };
+        found.push(Transfer { offset, asset });
+    }
+    found
+}
+
+/// Writes to state variables in the function body: `x = ...`, `x[k] += ...`, `x++`, `delete x[k]`.
+fn state_writes(masked: &str, outline: &SourceOutline, function: &FunctionSpan) -> Vec<(String, usize)> {
+    let write = Regex::new(r"\bdelete\s+([A-Za-z_$][\w$]*)|\b([A-Za-z_$][\w$]*)\s*(?:\[[^\]]*\]\s*)*(?:\+\+|--|[-+*/]?=[^=])").unwrap();
+    let body = function.body.clone();
+    write.captures_iter(&masked[body.clone()])
+        .filter_map(|c| {
+            let name = c.get(1).or(c.get(2)).unwrap().as_str();
+            outline.state_variable(name).filter(|v| !v.constant && !v.immutable).map(|_| (name.to_string(), body.start + c.get(0).unwrap().start()))
+        })
+        .collect()
+}
@@ -0,0 +1,202 @@
+use regex::Regex;
+
+use sentinel_common::solidity::{line_at, matching_close, pragma_range, FunctionKind, FunctionSpan, SourceOutline};
+
+use crate::call_graph::CallGraph;
+use crate::PrecompileIssue;
+
+/// The flows of an ACP-77 validator manager: each is initiated on the L1 and completed with a
+/// Warp-signed P-Chain message.
+struct ValidatorManager<'a> {
+    registrations: Vec<&'a FunctionSpan>,
+    weight_updates: Vec<&'a FunctionSpan>,
+    removals: Vec<&'a FunctionSpan>,
+    // Functions that read a verified Warp message.
+    warp_readers: Vec<&'a FunctionSpan>,
+}
+
+impl ValidatorManager<'_> {
+    fn flows(&self) -> impl Iterator<Item = &FunctionSpan> {
+        self.registrations.iter().chain(&self.weight_updates).chain(&self.removals).copied()
+    }
+}
+
+/// Recognises contracts that manage an L1's validators through Warp messages to the P-Chain
+/// (ACP-77 PoA and PoS validator managers) and checks that P-Chain messages are authenticated,
+/// weight changes are rate limited, validators cannot be registered twice and weights cannot
+/// underflow.
+pub fn report_validator_manager(masked: &str, outline: &SourceOutline, graph: &CallGraph, issues: &mut Vec<PrecompileIssue>) {
+    let Some(manager) = recognise(masked, outline) else { return };
+    let flows: Vec<String> = [("registration", &manager.registrations), ("weight updates", &manager.weight_updates), ("removal", &manager.removals)].iter()
+        .filter(|(_, functions)| !functions.is_empty())
+        .map(|(flow, functions)| format!("{} through {}", flow, functions.iter().map(|f| format!("`{}`", f.name)).collect::<Vec<_>>().join(", ")))
+        .collect();
+    let stake = Regex::new(r"(?i)stake|delegat").unwrap();
+    let kind = if manager.registrations.iter().any(|f| f.header.contains("payable") || stake.is_match(&f.name)) { "proof-of-stake" } else { "proof-of-authority" };
+    let first = manager.flows().map(|f| f.start_line).min().unwrap_or(0);
+    issues.push(PrecompileIssue {
+        line: first,
+        issue_type: "Validator Manager Pattern".to_string(),
+        description: format!("The contract looks like a {} validator manager for an L1 (ACP-77): {}.", kind, flows.join("; ")),
+        recommendation: "The P-Chain only applies validator changes it receives as Warp messages signed by the L1, and the L1 only learns of them from Warp messages signed by the P-Chain. Review the message handling checks below.".to_string(),
+    });
+
+    report_message_origin(masked, &manager, graph, issues);
+    report_churn(masked, &manager, issues);
+    report_reregistration(masked, &manager, graph, issues);
+    report_weight_underflow(masked, &manager, graph, issues);
+}
+
+fn recognise<'a>(masked: &str, outline: &'a SourceOutline) -> Option<ValidatorManager<'a>> {
+    let registration = Regex::new(r"(?i)ValidatorRegistration|registerValidator|^(initiate|complete)?(Validator|Delegator)Registration").unwrap();
+    let weight_update = Regex::new(r"(?i)ValidatorWeight|(set|update|change)Weight").unwrap();
+    let removal = Regex::new(r"(?i)ValidatorRemoval|removeValidator|(end|exit)Validation|(initiate|complete)EndValidation").unwrap();
+    let warp = Regex::new(r"\bgetVerifiedWarpMessage\s*\(").unwrap();
+
+    let functions: Vec<&FunctionSpan> = outline.functions.iter().filter(|f| f.kind == FunctionKind::Function).collect();
+    let matching = |pattern: &Regex| functions.iter().copied().filter(|f| pattern.is_match(&f.name)).collect::<Vec<_>>();
+    let manager = ValidatorManager {
+        registrations: matching(&registration),
+        weight_updates: matching(&weight_update),
+        removals: matching(&removal),
+        warp_readers: functions.iter().copied().filter(|f| warp.is_match(&masked[f.body.clone()])).collect(),
+    };
+    let flows = [&manager.registrations, &manager.weight_updates, &manager.removals].iter().filter(|f| !f.is_empty()).count();
+    let sends_warp = Regex::new(r"\bsendWarpMessage\s*\(").unwrap().is_match(masked);
+    (flows >= 2 || (flows == 1 && (sends_warp || !manager.warp_readers.is_empty()))).then_some(manager)
+}
+
+/// P-Chain messages must be verified, come from the P-Chain (source chain ID zero) and have no
+/// origin sender; otherwise a message from any chain or contract the L1 accepts Warp from can
+/// complete a validator change.
+fn report_message_origin(masked: &str, manager: &ValidatorManager, graph: &CallGraph, issues: &mut Vec<PrecompileIssue>) {
+    let compared = |field: &str| Regex::new(&format!(r"\.\s*{field}\s*(==|!=)|(==|!=)\s*[\w$.]*\.\s*{field}\b")).unwrap();
+    let source_chain = compared("sourceChainID");
+    let origin_sender = compared("originSenderAddress");
+    let valid_binding = Regex::new(r"\bbool\s+([A-Za-z_$][\w$]*)\s*\)\s*=\s*[^;]*\bgetVerifiedWarpMessage\s*\(").unwrap();
+
+    for &reader in &manager.warp_readers {
+        let bodies: Vec<&str> = graph.reachable_from(reader).iter().map(|f| &masked[f.body.clone()]).collect();
+        let body = &masked[reader.body.clone()];
+        let mut missing: Vec<&str> = Vec::new();
+        let checks_valid = valid_binding.captures(body).is_some_and(|c| {
+            let name = regex::escape(&c[1]);
+            Regex::new(&format!(r"(require|assert)\s*\(\s*{name}\b|if\s*\(\s*!\s*{name}\b|\b{name}\s*==\s*false")).unwrap().is_match(body)
+        });
+        if !checks_valid {
+            missing.push("the `valid` flag");
+        }
+        if !bodies.iter().any(|b| source_chain.is_match(b)) {
+            missing.push("`sourceChainID` against the P-Chain's blockchain ID (zero)");
+        }
+        if !bodies.iter().any(|b| origin_sender.is_match(b)) {
+            missing.push("`originSenderAddress` against `address(0)`");
+        }
+        if missing.is_empty() {
+            continue;
+        }
+        issues.push(PrecompileIssue {
+            line: reader.start_line,
+            issue_type: "Unvalidated Warp Message Origin".to_string(),
+            description: format!("`{}` reads a Warp message without checking {}. A message signed by another chain, or sent by a contract on one, can then complete a validator registration, weight update or removal.", reader.name, missing.join(", ")),
+            recommendation: "Revert unless the message is valid, `sourceChainID` is the P-Chain's blockchain ID (`bytes32(0)`) and `originSenderAddress` is `address(0)`, as the reference `ValidatorManager` does.".to_string(),
+        });
+    }
+
+    // Completions that never read a verified message take the P-Chain's word from the caller.
+    for completion in manager.flows().filter(|f| f.name.to_lowercase().starts_with("complete")) {
+        if graph.reachable_from(completion).iter().any(|f| manager.warp_readers.iter().any(|r| r.body == f.body)) {
+            continue;
+        }
+        issues.push(PrecompileIssue {
+            line: completion.start_line,
+            issue_type: "Unvalidated Warp Message Origin".to_string(),
+            description: format!("`{}` completes a validator change without reading a verified Warp message, so the P-Chain's acknowledgement is whatever the caller claims.", completion.name),
+            recommendation: "Take a Warp message index and read the P-Chain's acknowledgement with `getVerifiedWarpMessage`, then check its origin before updating validator state.".to_string(),
+        });
+    }
+}
+
+/// Without a churn limit, one call can move a large share of the total weight at once.
+fn report_churn(masked: &str, manager: &ValidatorManager, issues: &mut Vec<PrecompileIssue>) {
+    let churn = Regex::new(r"(?i)churn").unwrap();
+    if churn.is_match(masked) {
+        return;
+    }
+    let Some(first) = manager.flows().min_by_key(|f| f.start_line) else { return };
+    issues.push(PrecompileIssue {
+        line: first.start_line,
+        issue_type: "Missing Validator Churn Limit".to_string(),
+        description: "The validator manager does not limit how much of the total weight can change within a period. A compromised owner, or a large staker on a proof-of-stake L1, can replace most of the validator set at once and take over consensus before anyone reacts.".to_string(),
+        recommendation: "Track weight changes per churn period and revert registrations, weight updates and removals that exceed a maximum percentage of the total weight (`maximumChurnPercentage` in the reference `ValidatorManager`).".to_string(),
+    });
+}
+
+/// Registration must reject a NodeID that already has a validation, and completions must only
+/// apply to validations still pending, so a replayed message cannot apply twice.
+fn report_reregistration(masked: &str, manager: &ValidatorManager, graph: &CallGraph, issues: &mut Vec<PrecompileIssue>) {
+    let node_parameter = Regex::new(r"(?i)\b(\w*nodeID\w*)\s*[,)]").unwrap();
+    let status_check = Regex::new(r"(?i)(require\s*\(|\bif\s*\()[^;{]*(status|pending|registered|active)").unwrap();
+    for &registration in &manager.registrations {
+        let bodies: Vec<&str> = graph.reachable_from(registration).iter().map(|f| &masked[f.body.clone()]).collect();
+        let finding = if let Some(c) = node_parameter.captures(&registration.header) {
+            let name = regex::escape(&c[1]);
+            let checked = Regex::new(&format!(r"(require\s*\(|\bif\s*\()[^;{{]*\[[^\]]*\b{name}\b")).unwrap();
+            (!bodies.iter().any(|b| checked.is_match(b)))
+                .then(|| format!("`{}` does not check whether `{}` already has a validation, so the same node can be registered twice and its weight counted twice.", registration.name, &c[1]))
+        } else if registration.name.to_lowercase().starts_with("complete") {
+            (!bodies.iter().any(|b| status_check.is_match(b)))
+                .then(|| format!("`{}` does not check that the validation is still pending before marking it registered, so replaying the P-Chain's message applies the registration again.", registration.name))
+        } else {
+            None
+        };
+        let Some(description) = finding else { continue };
+        issues.push(PrecompileIssue {
+            line: registration.start_line,
+            issue_type: "Validator Re-registration".to_string(),
+            description,
+            recommendation: "Map each NodeID to its validation ID and revert if it is already registered, and move each validation through an explicit status (PendingAdded, Active, PendingRemoved, Completed) that completions must check.".to_string(),
+        });
+    }
+}
+
+/// Subtractions from validator or total weights that can wrap (unchecked or before Solidity
+/// 0.8) or revert permanently when the stored weights are out of sync.
+fn report_weight_underflow(masked: &str, manager: &ValidatorManager, graph: &CallGraph, issues: &mut Vec<PrecompileIssue>) {
+    let subtraction = Regex::new(r"(?i)\b([\w$]*weight[\w$]*)\s*(?:\[[^\]]*\]\s*)*(?:\.\s*[\w$]+\s*)?-=|\b([\w$]*weight[\w$]*)\s*(?:\[[^\]]*\]\s*)*(?:\.\s*[\w$]+\s*)?=\s*[^;=]*-").unwrap();
+    // Only code that no 0.8 compiler accepts is assumed to wrap; a range reaching 0.8 gets checked arithmetic.
+    let wrapping = Regex::new(r"\bpragma\s+solidity\s+([^;]+);").unwrap().captures(masked)
+        .and_then(|c| pragma_range(&c[1]))
+        .is_some_and(|(_, highest)| highest < (0, 8, 0));
+    let unchecked: Vec<std::ops::Range<usize>> = Regex::new(r"\bunchecked\s*\{").unwrap().find_iter(masked)
+        .filter_map(|m| matching_close(masked, m.end() - 1).map(|close| m.end()..close))
+        .collect();
+
+    let mut seen: Vec<usize> = Vec::new();
+    for &flow in manager.weight_updates.iter().chain(&manager.removals) {
+        for function in graph.reachable_from(flow) {
+            for c in subtraction.captures_iter(&masked[function.body.clone()]) {
+                let offset = function.body.start + c.get(0).unwrap().start();
+                if seen.contains(&offset) {
+                    continue;
+                }
+                seen.push(offset);
+                let variable = c.get(1).or(c.get(2)).unwrap().as_str();
+                let guarded = Regex::new(&format!(r"\b{}\b[^;{{]*(>=|<=|<|>)|(>=|<=|<|>)[^;{{]*\b{}\b", regex::escape(variable), regex::escape(variable))).unwrap();
+                let description = if wrapping || unchecked.iter().any(|block| block.contains(&offset)) {
+                    format!("`{}` subtracts from `{}` with wrapping arithmetic{}. If the amount removed exceeds the stored weight, it wraps to a huge value and the L1's weight accounting, and the churn limit based on it, break.", function.name, variable, if wrapping { " (Solidity before 0.8)" } else { " (`unchecked`)" })
+                } else if !guarded.is_match(&masked[function.body.clone()]) {
+                    format!("`{}` subtracts from `{}` without checking that it covers the amount removed. If the stored weights are out of sync (e.g. weight updates acknowledged out of order), the subtraction reverts and the validator can never be removed; nothing keeps the total weight above zero either.", function.name, variable)
+                } else {
+                    continue;
+                };
+                issues.push(PrecompileIssue {
+                    line: line_at(masked, offset),
+                    issue_type: "Validator Weight Underflow".to_string(),
+                    description,
+                    recommendation: "Use checked arithmetic, apply weight changes in the order of their P-Chain nonces, check that the weight being removed is at most the stored weight, and refuse to remove the last of the L1's weight.".to_string(),
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use sentinel_common::address_bindings::AddressBindings;
+use sentinel_common::address_literals::{self, AddressClass, AddressLiteral};
+use sentinel_common::addresses::{AddressRegistry, DeploymentStatus};
+use sentinel_common::precompiles::Precompile;
+use sentinel_common::solidity::{mask_comments_and_strings, SourceOutline};
+
+use crate::genesis::PrecompileStatus;
+use crate::target::{TargetChain, TargetCode};
+use crate::{PortabilityIssue, Registries, Severity};
+
+pub struct AddressCheckContext<'a> {
+    pub registries: &'a Registries,
+    pub target_chain: Option<&'a TargetChain>,
+    pub target_network: Option<&'a str>,
+    // Status per precompile address; `None` when the job carries no genesis or upgrade config.
+    pub precompile_statuses: Option<&'a HashMap<String, PrecompileStatus>>,
+    pub deployment_timestamp: u64,
+}
+
+/// Reports every hard-coded address literal once, at its first occurrence. Precompile findings
+/// are reported at each call made through the literal or a constant, immutable or local holding it.
+pub fn report_address_literals(source: &str, ctx: &AddressCheckContext, issues: &mut Vec<PortabilityIssue>) {
+    let masked = mask_comments_and_strings(source);
+    let calls = AddressBindings::collect(&masked, &SourceOutline::parse(source)).calls(&masked);
+
+    for literal in address_literals::extract_address_literals(source) {
+        if let Some((line, written)) = &literal.invalid_checksum {
+            issues.push(PortabilityIssue {
+                line: *line,
+                issue_type: "Invalid Address Checksum".to_string(),
+                severity: Severity::High,
+                description: format!("The address literal {} does not match its EIP-55 checksum. solc rejects such literals.", written),
+                recommendation: format!("Use the checksummed form {}, after double-checking it is the intended address.", address_literals::to_checksum_address(&literal.address)),
+            });
+        }
+
+        match address_literals::classify(&literal.address, &ctx.registries.precompiles, &ctx.registries.addresses) {
+            // Zero and burn addresses are the same on every chain.
+            AddressClass::Zero | AddressClass::Dead => {}
+            AddressClass::Precompile(precompile) => {
+                if let Some(status) = ctx.precompile_statuses.and_then(|s| s.get(&literal.address)) {
+                    let mut lines: Vec<u32> = calls.iter().filter(|c| c.address == literal.address).map(|c| c.line).collect();
+                    lines.dedup();
+                    // An address that is stored but never called through is reported where it is written.
+                    if lines.is_empty() {
+                        lines.push(literal.first_line());
+                    }
+                    for line in lines {
+                        report_precompile_status(line, precompile, status, ctx.deployment_timestamp, issues);
+                    }
+                }
+            }
+            AddressClass::UnregisteredPrecompile => {
+                if ctx.precompile_statuses.is_some() {
+                    issues.push(PortabilityIssue {
+                        line: literal.first_line(),
+                        issue_type: "Unknown Precompile".to_string(),
+                        severity: Severity::Medium,
+                        description: format!("The address {} is in a reserved precompile range but is not in the precompile registry{}.", literal.address, occurrence_note(&literal)),
+                        recommendation: "If this is a custom stateful precompile of the target Subnet, register it (see SENTINEL_PRECOMPILE_REGISTRY) so its activation can be checked.".to_string(),
+                    });
+                }
+            }
+            class => match ctx.target_chain {
+                Some(target) => report_target_address(&literal, target, &ctx.registries.addresses, issues),
+                None if matches!(class, AddressClass::KnownProtocol) => report_known_address(&literal, ctx.target_network, &ctx.registries.addresses, issues),
+                None => issues.push(PortabilityIssue {
+                    line: literal.first_line(),
+                    issue_type: "Hardcoded Address".to_string(),
+                    severity: Severity::Low,
+                    description: format!("The address {} is hard-coded in `{}`{}. It looks like an EOA or an unregistered contract.", literal.address, context(&literal), occurrence_note(&literal)),
+                    recommendation: "Addresses rarely carry over between chains. Pass it in the constructor or a setter, or register it in the known-address registry if it is a protocol deployment.".to_string(),
+                }),
+            },
+        }
+    }
+}
+
+pub fn report_precompile_status(line: u32, precompile: &Precompile, status: &PrecompileStatus, deployment_timestamp: u64, issues: &mut Vec<PortabilityIssue>) {
+    let name = &precompile.name;
+    match status {
+        PrecompileStatus::Active { removal_at: None } => {}
+        PrecompileStatus::NotConfigured => issues.push(PortabilityIssue {
+            line,
+            issue_type: "Precompile Mismatch".to_string(),
+            severity: Severity::Critical,
+            description: format!("Contract interacts with the '{}' precompile, but it is NOT enabled in the provided Subnet genesis.", name),
+            recommendation: "Ensure your target Subnet's genesis file enables all precompiles your contracts require.".to_string(),
+        }),
+        PrecompileStatus::NotYetActive { activates_at } => issues.push(PortabilityIssue {
+            line,
+            issue_type: "Precompile Not Yet Active".to_string(),
+            severity: Severity::High,
+            description: format!("Contract interacts with the '{}' precompile, which only activates at timestamp {} (deployment time: {}).", name, activates_at, deployment_timestamp),
+            recommendation: "Calls to this precompile will revert until its activation timestamp. Deploy after activation or gate the calls behind a timestamp check.".to_string(),
+        }),
+        PrecompileStatus::Disabled { since, reenabled_at } => issues.push(PortabilityIssue {
+            line,
+            issue_type: "Precompile Disabled".to_string(),
+            severity: Severity::Critical,
+            description: match reenabled_at {
+                Some(ts) => format!("Contract interacts with the '{}' precompile, which was disabled at timestamp {} and is only re-enabled at timestamp {}.", name, since, ts),
+                None => format!("Contract interacts with the '{}' precompile, which was disabled at timestamp {} by a precompile upgrade.", name, since),
+            },
+            recommendation: "Calls to a disabled precompile revert. Remove the dependency or coordinate with the Subnet operators to re-enable it.".to_string(),
+        }),
+        PrecompileStatus::Active { removal_at: Some(ts) } => issues.push(PortabilityIssue {
+            line,
+            issue_type: "Precompile Scheduled Removal".to_string(),
+            severity: Severity::Medium,
+            description: format!("Contract interacts with the '{}' precompile, which is scheduled to be disabled at timestamp {}.", name, ts),
+            recommendation: "Calls will start reverting once the precompile is disabled. Plan a migration path or make the dependency optional.".to_string(),
+        }),
+    }
+}
+
+fn report_known_address(literal: &AddressLiteral, target_network: Option<&str>, addresses: &AddressRegistry, issues: &mut Vec<PortabilityIssue>) {
+    let matches: Vec<_> = addresses.lookup(&literal.address).collect();
+    if matches.iter().any(|(b, _)| Some(b.network.as_str()) == target_network) {
+        return;
+    }
+    let Some((_, known)) = matches.first() else { return };
+    let networks: Vec<&str> = matches.iter().map(|(b, _)| b.network.as_str()).collect();
+    let deprecated = matches.iter().any(|(_, k)| k.status == DeploymentStatus::Deprecated);
+    issues.push(PortabilityIssue {
+        line: literal.first_line(),
+        issue_type: if networks.contains(&"cchain-mainnet") { "C-Chain Dependency" } else { "Network Address Dependency" }.to_string(),
+        severity: Severity::High,
+        description: format!(
+            "A hardcoded address for a known protocol ({}, {}) on {} was found in `{}`{}.{}",
+            known.protocol, known.category, networks.join(", "), context(literal), occurrence_note(literal),
+            if deprecated { " The registry marks this deployment as deprecated." } else { "" },
+        ),
+        recommendation: "This contract will not exist on a new Subnet. Pass protocol addresses in the constructor or a setter function to make your contract portable.".to_string(),
+    });
+}
+
+fn report_target_address(literal: &AddressLiteral, target: &TargetChain, addresses: &AddressRegistry, issues: &mut Vec<PortabilityIssue>) {
+    let address = &literal.address;
+    let known: Vec<_> = addresses.lookup(address).collect();
+    let label = known.first().map(|(_, k)| format!(" ({})", k.protocol)).unwrap_or_default();
+    let reference = known.iter().find_map(|(book, k)| k.code_hash.as_ref().map(|h| (book.network.as_str(), h.to_lowercase())));
+    let line = literal.first_line();
+    let note = occurrence_note(literal);
+
+    match (target.code_at(address), reference) {
+        (TargetCode::Empty, _) => issues.push(PortabilityIssue {
+            line,
+            issue_type: "Address Empty On Target".to_string(),
+            severity: Severity::High,
+            description: format!("The hardcoded address {}{} has no code on {}{}.", address, label, target.name, note),
+            recommendation: "High-level calls to this address will revert, and low-level calls will silently succeed without doing anything. Deploy the dependency on the target chain and pass its address in the constructor or a setter.".to_string(),
+        }),
+        (TargetCode::Code { code_hash: Some(actual) }, Some((network, expected))) if actual != expected => issues.push(PortabilityIssue {
+            line,
+            issue_type: "Address Code Mismatch On Target".to_string(),
+            severity: Severity::High,
+            description: format!("The hardcoded address {}{} has code on {}, but it differs from the code on {} (code hash {} vs. {}){}.", address, label, target.name, network, actual, expected, note),
+            recommendation: "A different contract lives at this address on the target chain. Verify its interface and behaviour before relying on it, or make the address configurable.".to_string(),
+        }),
+        (TargetCode::Code { .. }, _) => issues.push(PortabilityIssue {
+            line,
+            issue_type: "Address Present On Target".to_string(),
+            severity: Severity::Low,
+            description: format!("The hardcoded address {}{} has code on {}{}.", address, label, target.name, note),
+            recommendation: "The dependency exists on the target chain. Confirm it is the same protocol version you tested against.".to_string(),
+        }),
+    }
+}
+
+fn context(literal: &AddressLiteral) -> String {
+    let line = literal.occurrences.first().map(|o| o.context.as_str()).unwrap_or_default();
+    if line.chars().count() > 100 {
+        format!("{}...", line.chars().take(100).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+fn occurrence_note(literal: &AddressLiteral) -> String {
+    let others = literal.other_lines();
+    if others.is_empty() {
+        String::new()
+    } else {
+        format!(" (also used on line{} {})", if others.len() > 1 { "s" } else { "" }, others.iter().map(|l| l.to_string()).collect::<Vec<_>>().join(", "))
+    }
+}
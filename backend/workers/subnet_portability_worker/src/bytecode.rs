@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use sentinel_common::evm::{self, Instruction, SourceMapEntry};
+use sentinel_common::solidity::line_at;
+
+use crate::address_checks;
+use crate::deployer_allow_list::{self, FactoryKind};
+use crate::evm_compat::TargetEvm;
+use crate::genesis::{Genesis, PrecompileStatus, UpgradeConfig};
+use crate::native_token::{self, NativeTokenSemantics, NativeUse};
+use crate::{PortabilityIssue, Registries, Severity};
+
+/// Runtime bytecode as a hex string, or a Foundry/Hardhat/solc artifact JSON carrying it.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum BytecodeInput {
+    Hex(String),
+    Artifact(Value),
+}
+
+/// Decoded runtime code and, when the artifact has one, its source map.
+pub struct RuntimeCode {
+    pub code: Vec<u8>,
+    source_map: Vec<SourceMapEntry>,
+    // Index of the analysed file in the source map, when the artifact's AST names it.
+    source_file: Option<i64>,
+}
+
+impl BytecodeInput {
+    pub fn runtime_code(&self) -> Result<RuntimeCode, String> {
+        let (object, source_map, source_file) = match self {
+            BytecodeInput::Hex(hex) => (hex.as_str(), None, None),
+            BytecodeInput::Artifact(artifact) => {
+                // Foundry: {"deployedBytecode": {"object", "sourceMap"}}; Hardhat: {"deployedBytecode": "0x.."};
+                // solc standard JSON: {"evm": {"deployedBytecode": {"object", "sourceMap"}}}.
+                let deployed = artifact_field(artifact, "deployedBytecode").ok_or("artifact has no deployedBytecode")?;
+                let object = deployed.as_str().or_else(|| deployed.get("object").and_then(Value::as_str)).ok_or("deployedBytecode has no object")?;
+                let source_map = deployed.get("sourceMap").and_then(Value::as_str);
+                let source_file = artifact.pointer("/ast/src").and_then(Value::as_str).and_then(|src| src.rsplit(':').next()?.parse().ok());
+                (object, source_map, source_file)
+            }
+        };
+        RuntimeCode::new(&decode_object(object)?, source_map, source_file)
+    }
+
+    /// The creation code, which only artifacts carry.
+    pub fn init_code(&self) -> Option<Vec<u8>> {
+        let BytecodeInput::Artifact(artifact) = self else { return None };
+        let bytecode = artifact_field(artifact, "bytecode")?;
+        decode_object(bytecode.as_str().or_else(|| bytecode.get("object").and_then(Value::as_str))?).ok()
+    }
+
+    /// The contract name, for artifact formats that record it (Hardhat's `contractName`).
+    pub fn contract_name(&self) -> Option<&str> {
+        let BytecodeInput::Artifact(artifact) = self else { return None };
+        artifact.get("contractName").and_then(Value::as_str)
+    }
+}
+
+fn artifact_field<'a>(artifact: &'a Value, name: &str) -> Option<&'a Value> {
+    artifact.get(name).or_else(|| artifact.get("evm")?.get(name))
+}
+
+fn decode_object(object: &str) -> Result<Vec<u8>, String> {
+    // Unlinked library placeholders (`__$<hash>$__`) stand in for 20-byte addresses.
+    let linked = Regex::new(r"__\$[0-9a-fA-F]{34}\$__").unwrap().replace_all(object, "0".repeat(40));
+    let code = sentinel_common::decode_hex(&linked).ok_or("bytecode is not valid hex")?;
+    if code.is_empty() {
+        return Err("bytecode is empty".to_string());
+    }
+    Ok(code)
+}
+
+impl RuntimeCode {
+    pub fn new(code: &[u8], source_map: Option<&str>, source_file: Option<i64>) -> Result<Self, String> {
+        if code.is_empty() {
+            return Err("bytecode is empty".to_string());
+        }
+        Ok(RuntimeCode { code: code.to_vec(), source_map: source_map.map(evm::parse_source_map).unwrap_or_default(), source_file })
+    }
+
+    /// Offset into the analysed source of the instruction at `index`, if the source map covers it.
+    pub fn source_offset(&self, index: usize, source: &str) -> Option<usize> {
+        self.source_map.get(index)
+            .filter(|e| e.start >= 0 && e.file >= 0 && self.source_file.is_none_or(|f| f == e.file))
+            .map(|e| e.start as usize)
+            .filter(|start| *start < source.len() && source.is_char_boundary(*start))
+    }
+}
+
+pub struct BytecodeContext<'a> {
+    pub registries: &'a Registries,
+    pub precompile_statuses: Option<&'a HashMap<String, PrecompileStatus>>,
+    pub target_evm: Option<&'a TargetEvm>,
+    pub native_token: &'a NativeTokenSemantics,
+    pub genesis: Option<&'a Genesis>,
+    pub upgrade_config: Option<&'a UpgradeConfig>,
+    pub deployment_timestamp: u64,
+}
+
+/// Runs the bytecode-grounded checks. Findings map back to source lines through the source
+/// map when there is one; otherwise they are reported on line 0 with their bytecode offset.
+pub fn report_bytecode(runtime: &RuntimeCode, source: &str, ctx: &BytecodeContext, issues: &mut Vec<PortabilityIssue>) {
+    let instructions = evm::disassemble(evm::strip_metadata(&runtime.code));
+    let locate = |index: usize| -> (u32, String) {
+        let offset = instructions[index].offset;
+        let line = runtime.source_offset(index, source).map(|start| line_at(source, start)).unwrap_or(0);
+        (line, format!("bytecode offset 0x{:04x}", offset))
+    };
+
+    report_chainid(&instructions, &locate, issues);
+    report_native_value(&instructions, &locate, ctx.native_token, issues);
+    report_precompile_calls(&instructions, &locate, ctx, issues);
+
+    let factories: Vec<(u32, FactoryKind)> = instructions.iter().enumerate()
+        .filter_map(|(i, ins)| match ins.byte {
+            0xf0 => Some((locate(i).0, FactoryKind::BytecodeCreate)),
+            0xf5 => Some((locate(i).0, FactoryKind::BytecodeCreate2)),
+            _ => None,
+        })
+        .fold(Vec::new(), |mut sites, site| {
+            if !sites.contains(&site) {
+                sites.push(site);
+            }
+            sites
+        });
+    deployer_allow_list::report_factory_sites(&factories, ctx.genesis, ctx.upgrade_config, ctx.deployment_timestamp, issues);
+
+    if let Some(target) = ctx.target_evm {
+        report_unsupported_opcodes(&instructions, &locate, target, issues);
+    }
+}
+
+fn report_chainid(instructions: &[Instruction], locate: &dyn Fn(usize) -> (u32, String), issues: &mut Vec<PortabilityIssue>) {
+    for i in (0..instructions.len()).filter(|i| instructions[*i].is("CHAINID")) {
+        // `PUSH <id> CHAINID EQ` / `CHAINID PUSH <id> EQ`, possibly with a DUP or SWAP in between.
+        let window = &instructions[i.saturating_sub(2)..(i + 4).min(instructions.len())];
+        let compared = window.iter().any(|w| w.is("EQ"));
+        let constant = window.iter().filter_map(|w| w.push_value()).map(be_to_u128).find(|v| *v > 0);
+        let (line, offset) = locate(i);
+        match (compared, constant) {
+            (true, Some(id)) => issues.push(PortabilityIssue {
+                line,
+                issue_type: "Hardcoded Chain Assumption".to_string(),
+                severity: Severity::High,
+                description: format!("The bytecode compares `CHAINID` against the constant {} at {}. This branch behaves differently, or reverts, on any other chain.", id, offset),
+                recommendation: "Do not gate logic on a specific chain id. Pass chain-specific behaviour in as configuration.".to_string(),
+            }),
+            _ => issues.push(PortabilityIssue {
+                line,
+                issue_type: "Chain ID Read".to_string(),
+                severity: Severity::Low,
+                description: format!("The bytecode reads `CHAINID` at {}. Confirm it is recomputed per call (e.g. for an EIP-712 domain separator) rather than cached or compared to a fixed id.", offset),
+                recommendation: "Analyse the source, if available, to classify how the chain id is used.".to_string(),
+            }),
+        }
+    }
+}
+
+fn report_native_value(instructions: &[Instruction], locate: &dyn Fn(usize) -> (u32, String), semantics: &NativeTokenSemantics, issues: &mut Vec<PortabilityIssue>) {
+    // Reported once per opcode, at its first use.
+    let mut uses: Vec<(&str, &str, NativeUse, usize, usize)> = Vec::new();
+    for (i, ins) in instructions.iter().enumerate() {
+        let (name, expr, usage) = match ins.opcode.map(|o| o.mnemonic) {
+            Some("SELFBALANCE") => ("SELFBALANCE", "address(this).balance", NativeUse::Accounting),
+            Some("BALANCE") => ("BALANCE", ".balance", NativeUse::Accounting),
+            // solc's non-payable guard is `CALLVALUE DUP1 ISZERO`; anything else reads the value.
+            Some("CALLVALUE") if instructions.get(i + 1).is_some_and(|n| n.is("DUP1")) && instructions.get(i + 2).is_some_and(|n| n.is("ISZERO")) => continue,
+            Some("CALLVALUE") => ("CALLVALUE", "msg.value", NativeUse::BenignRead),
+            _ => continue,
+        };
+        match uses.iter_mut().find(|u| u.0 == name) {
+            Some(entry) => entry.4 += 1,
+            None => uses.push((name, expr, usage, i, 1)),
+        }
+    }
+
+    for (name, expr, usage, first, count) in uses {
+        if usage == NativeUse::BenignRead && semantics.has_context() && !semantics.unfundable() {
+            continue;
+        }
+        let (severity, reasons) = native_token::assess(expr, usage, semantics);
+        let (line, offset) = locate(first);
+        issues.push(PortabilityIssue {
+            line,
+            issue_type: "Native Token Assumption".to_string(),
+            severity,
+            description: format!("The bytecode reads `{}` ({} time{}, first at {}). {}", name, count, if count > 1 { "s" } else { "" }, offset, reasons.join(" ")).trim_end().to_string(),
+            recommendation: "Confirm the logic still holds when the native token has a different value, supply or minting policy than AVAX.".to_string(),
+        });
+    }
+}
+
+fn report_precompile_calls(instructions: &[Instruction], locate: &dyn Fn(usize) -> (u32, String), ctx: &BytecodeContext, issues: &mut Vec<PortabilityIssue>) {
+    let Some(statuses) = ctx.precompile_statuses else { return };
+    let mut reported: Vec<String> = Vec::new();
+    for (i, ins) in instructions.iter().enumerate() {
+        // Stateful precompile addresses have a non-zero leading byte, so they are pushed as 20 bytes.
+        let Some(value) = ins.push_value().filter(|v| v.len() == 20) else { continue };
+        let address = sentinel_common::encode_hex(value);
+        let Some(precompile) = ctx.registries.precompiles.by_address(&address).filter(|p| !p.is_always_enabled()) else { continue };
+        if reported.contains(&address) {
+            continue;
+        }
+        reported.push(address.clone());
+        if let Some(status) = statuses.get(&address) {
+            address_checks::report_precompile_status(locate(i).0, precompile, status, ctx.deployment_timestamp, issues);
+        }
+    }
+}
+
+fn report_unsupported_opcodes(instructions: &[Instruction], locate: &dyn Fn(usize) -> (u32, String), target: &TargetEvm, issues: &mut Vec<PortabilityIssue>) {
+    let mut seen: Vec<u8> = Vec::new();
+    for (i, ins) in instructions.iter().enumerate() {
+        let Some(opcode) = ins.opcode else { continue };
+        let Some((severity, reason)) = target.assess(opcode.since) else { continue };
+        if seen.contains(&ins.byte) {
+            continue;
+        }
+        seen.push(ins.byte);
+        let count = instructions.iter().filter(|other| other.byte == ins.byte).count();
+        let (line, offset) = locate(i);
+        issues.push(PortabilityIssue {
+            line,
+            issue_type: "Unsupported Opcode".to_string(),
+            severity,
+            description: format!("The bytecode uses `{}` ({} time{}, first at {}), introduced in {}. {}", opcode.name(), count, if count > 1 { "s" } else { "" }, offset, opcode.since.name(), reason),
+            recommendation: format!("Recompile with `evmVersion` set to \"{}\" or lower, or deploy after the target activates the required upgrade.", target.active.name()),
+        });
+    }
+}
+
+fn be_to_u128(bytes: &[u8]) -> u128 {
+    bytes.iter().rev().take(16).rev().fold(0u128, |acc, b| (acc << 8) | *b as u128)
+}
@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+
+use regex::Regex;
+
+use sentinel_common::addresses::AddressRegistry;
+use sentinel_common::solidity::{self, line_at, mask_comments_and_strings, FunctionKind, FunctionSpan, SourceOutline};
+
+use crate::{PortabilityIssue, Severity};
+
+/// Classifies how the chain id flows through the contract and reports only the patterns that
+/// break when it changes: comparisons against fixed ids, and ids (or domain separators) cached
+/// at construction without a recomputation guard. Reading `block.chainid` at call time, e.g.
+/// when building an EIP-712 domain separator, is the portable pattern and is not reported.
+pub fn report_chainid_usage(source: &str, addresses: &AddressRegistry, issues: &mut Vec<PortabilityIssue>) {
+    let chainid_regex = Regex::new(r"\bblock\s*\.\s*chainid\b|\bchainid\s*\(\s*\)").unwrap();
+    let word_regex = Regex::new(r"[A-Za-z_$][\w$]*").unwrap();
+    let comparison_regex = Regex::new(r"([\w$\.]+(?:\s*\(\s*\))?)\s*(==|!=)\s*([\w$\.]+(?:\s*\(\s*\))?)").unwrap();
+    let assembly_eq_regex = Regex::new(r"\beq\s*\(\s*([\w$]+(?:\s*\(\s*\))?)\s*,\s*([\w$]+(?:\s*\(\s*\))?)\s*\)").unwrap();
+
+    let masked = mask_comments_and_strings(source);
+    let lines: Vec<&str> = masked.lines().collect();
+    let outline = SourceOutline::parse(source);
+
+    // State variables holding the chain id, or a value derived from it, fixed at construction.
+    let mut cached: Vec<(String, u32)> = Vec::new();
+    for variable in &outline.state_variables {
+        if !variable.constant && variable.initializer.as_deref().is_some_and(|init| chainid_regex.is_match(init)) {
+            cached.push((variable.name.clone(), variable.line));
+        }
+    }
+    for constructor in outline.functions.iter().filter(|f| f.kind == FunctionKind::Constructor) {
+        for (name, offset) in chainid_assignments(&masked, constructor, &chainid_regex, &word_regex) {
+            if outline.state_variable(&name).is_some() && !cached.iter().any(|(cached, _)| *cached == name) {
+                cached.push((name, line_at(&masked, offset)));
+            }
+        }
+    }
+
+    // Per function, the variables assigned from the chain id and the line of each assignment.
+    let derived: Vec<Vec<(String, u32)>> = outline.functions.iter()
+        .map(|f| chainid_assignments(&masked, f, &chainid_regex, &word_regex).into_iter().map(|(name, offset)| (name, line_at(&masked, offset))).collect())
+        .collect();
+
+    let mut guard_lines: Vec<u32> = Vec::new();
+    let mut reported_lines: HashSet<u32> = HashSet::new();
+    for (i, text) in lines.iter().enumerate() {
+        let line = (i + 1) as u32;
+        // Locals assigned from the chain id, earlier in the enclosing function.
+        let aliases = outline.function_at_line(line).and_then(|f| outline.functions.iter().position(|g| std::ptr::eq(f, g))).map(|i| derived[i].as_slice()).unwrap_or_default();
+        let is_chainid = |operand: &str| chainid_regex.is_match(operand) || aliases.iter().any(|(a, at)| a == operand && *at <= line) || cached.iter().any(|(name, _)| name == operand);
+
+        let pairs = comparison_regex.captures_iter(text).map(|c| (c[1].to_string(), c[3].to_string()))
+            .chain(assembly_eq_regex.captures_iter(text).map(|c| (c[1].to_string(), c[2].to_string())));
+        for (left, right) in pairs {
+            let (id_side, other) = match (is_chainid(&left), is_chainid(&right)) {
+                (true, false) => (left, right),
+                (false, true) => (right, left),
+                (true, true) => {
+                    if [&left, &right].iter().any(|operand| cached.iter().any(|(name, _)| name == *operand)) {
+                        guard_lines.push(line);
+                    }
+                    continue;
+                }
+                (false, false) => continue,
+            };
+            let Some(id) = outline.resolve_uint(&other) else { continue };
+            if !reported_lines.insert(line) {
+                continue;
+            }
+            issues.push(PortabilityIssue {
+                line,
+                issue_type: "Hardcoded Chain Assumption".to_string(),
+                severity: Severity::High,
+                description: format!("The chain id (`{}`) is compared against the fixed value {}{}. This branch behaves differently, or reverts, on any other chain.", id_side, id, chain_label(id, addresses)),
+                recommendation: "Do not gate logic on a specific chain id. Pass chain-specific behaviour in as configuration, or compare against an id stored at deployment if the check is a replay guard.".to_string(),
+            });
+        }
+    }
+
+    // `block.chainid == _CACHED_CHAIN_ID ? _CACHED_DOMAIN_SEPARATOR : _build()`: every cached value
+    // read in the function holding the guard is revalidated on each use.
+    let guarded: HashSet<&str> = guard_lines.iter()
+        .map(|line| match outline.function_at_line(*line) {
+            Some(f) => &masked[f.body.clone()],
+            None => lines[*line as usize - 1],
+        })
+        .flat_map(|text| word_regex.find_iter(text).map(|m| m.as_str()))
+        .collect();
+    for (name, line) in &cached {
+        if guarded.contains(name.as_str()) {
+            continue;
+        }
+        let domain_separator = outline.state_variable(name).is_some_and(|v| v.type_name == "bytes32") || name.to_lowercase().contains("domain");
+        let immutable = outline.state_variable(name).is_some_and(|v| v.immutable);
+        issues.push(PortabilityIssue {
+            line: *line,
+            issue_type: "Cached Chain ID".to_string(),
+            severity: Severity::Medium,
+            description: if domain_separator {
+                format!("The EIP-712 domain separator `{}` is computed from the chain id once, at construction, and never recomputed.", name)
+            } else {
+                format!("The chain id is cached in {} `{}` at construction.", if immutable { "the immutable" } else { "the state variable" }, name)
+            } + " If the bytecode is placed in a Subnet genesis `alloc` (where the constructor never runs) or the chain forks to a new id, the cached value is stale and signatures or replay checks break.",
+            recommendation: "Recompute the value when `block.chainid` differs from the cached id, as OpenZeppelin's EIP712 does, or read `block.chainid` at call time.".to_string(),
+        });
+    }
+}
+
+/// Variables assigned, in `function`, a value computed from the chain id: directly, or through a
+/// local assigned from it earlier (`uint256 id = block.chainid; separator = keccak256(abi.encode(id))`).
+/// Each comes with the offset of the assignment statement.
+fn chainid_assignments(masked: &str, function: &FunctionSpan, chainid_regex: &Regex, word_regex: &Regex) -> Vec<(String, usize)> {
+    let mut assigned: Vec<(String, usize)> = Vec::new();
+    for statement in solidity::statements(masked, function.body.clone()) {
+        let text = &masked[statement.clone()];
+        let Some((name, value)) = solidity::assignment(text) else { continue };
+        let value = &text[value];
+        let derived = chainid_regex.is_match(value) || word_regex.find_iter(value).any(|w| assigned.iter().any(|(a, _)| a == w.as_str()));
+        if derived {
+            assigned.push((name.to_string(), statement.start));
+        }
+    }
+    assigned
+}
+
+fn chain_label(id: u64, addresses: &AddressRegistry) -> String {
+    let known = addresses.networks().find(|book| book.chain_id == Some(id)).map(|book| book.network.clone());
+    match (known, id) {
+        (Some(network), _) => format!(" ({})", network),
+        (None, 1) => " (Ethereum mainnet)".to_string(),
+        (None, _) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached_chain_ids(source: &str) -> Vec<u32> {
+        let mut issues = Vec::new();
+        report_chainid_usage(source, &AddressRegistry::default(), &mut issues);
+        issues.iter().filter(|i| i.issue_type == "Cached Chain ID").map(|i| i.line).collect()
+    }
+
+    #[test]
+    fn domain_separator_built_over_several_lines() {
+        let source = "contract A {\n    bytes32 DOMAIN_SEPARATOR;\n    constructor() {\n        DOMAIN_SEPARATOR = keccak256(\n            abi.encode(TYPEHASH, block.chainid, address(this))\n        );\n    }\n}\n";
+        assert_eq!(cached_chain_ids(source), vec![4]);
+    }
+
+    #[test]
+    fn domain_separator_built_through_a_local() {
+        let source = "contract A {\n    bytes32 DOMAIN_SEPARATOR;\n    constructor() {\n        uint256 id = block.chainid;\n        DOMAIN_SEPARATOR = keccak256(abi.encode(TYPEHASH, id, address(this)));\n    }\n}\n";
+        assert_eq!(cached_chain_ids(source), vec![5]);
+    }
+
+    #[test]
+    fn guarded_cache_is_not_reported() {
+        let source = "contract A {\n    uint256 immutable CACHED_ID;\n    bytes32 immutable CACHED_SEPARATOR;\n    constructor() {\n        uint256 id = block.chainid;\n        CACHED_ID = id;\n        CACHED_SEPARATOR = keccak256(abi.encode(id));\n    }\n    function separator() public view returns (bytes32) {\n        return block.chainid == CACHED_ID ? CACHED_SEPARATOR : keccak256(abi.encode(block.chainid));\n    }\n}\n";
+        assert!(cached_chain_ids(source).is_empty());
+    }
+
+    #[test]
+    fn local_alias_compared_against_fixed_id() {
+        let source = "contract A {\n    function f() public view {\n        uint256 id =\n            block.chainid;\n        require(id == 43114);\n    }\n}\n";
+        let mut issues = Vec::new();
+        report_chainid_usage(source, &AddressRegistry::default(), &mut issues);
+        assert_eq!(issues.iter().map(|i| (i.line, i.issue_type.as_str())).collect::<Vec<_>>(), vec![(5, "Hardcoded Chain Assumption")]);
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::env;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde_json::{json, Value};
+
+use sentinel_common::evm::{self, EvmFork};
+use sentinel_common::solidity::{line_at, mask_comments_and_strings, SourceOutline};
+
+use crate::bytecode::{BytecodeInput, RuntimeCode};
+use crate::evm_compat::TargetEvm;
+use crate::genesis::Genesis;
+use crate::{PortabilityIssue, Severity};
+
+// EIP-170 and EIP-3860 defaults.
+const MAX_CODE_SIZE: usize = 24_576;
+const MAX_INIT_CODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+
+// Contracts at or above this share of a limit are reported before they cross it.
+const NEAR_LIMIT_PERCENT: usize = 90;
+
+// Optimizer settings used when the worker compiles the source itself.
+const OPTIMIZER_RUNS: u64 = 200;
+
+/// Path to the solc binary used to compile source-only jobs. `solc` is looked up on PATH when unset.
+pub const SOLC_ENV_VAR: &str = "SENTINEL_SOLC";
+
+// A compile taking longer than this is killed, so one job cannot block the worker's job loop.
+const SOLC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Code size limits on the target chain.
+pub struct CodeSizeLimits {
+    pub runtime: usize,
+    // `None` when initcode size is not limited at deployment (the EVM level is below Shanghai).
+    pub init_code: Option<usize>,
+}
+
+impl CodeSizeLimits {
+    /// EIP-170/EIP-3860 limits, or the `maxCodeSize`/`maxInitCodeSize` a modified VM declares
+    /// in its genesis config.
+    pub fn from_job(genesis: Option<&Genesis>, target_evm: Option<&TargetEvm>) -> Self {
+        let configured = |key: &str| genesis.and_then(|g| g.config.extra.get(key)).and_then(Value::as_u64).map(|v| v as usize);
+        let init_code_enforced = target_evm.is_none_or(|t| t.availability(EvmFork::Shanghai).is_none());
+        CodeSizeLimits {
+            runtime: configured("maxCodeSize").unwrap_or(MAX_CODE_SIZE),
+            init_code: init_code_enforced.then(|| configured("maxInitCodeSize").unwrap_or(MAX_INIT_CODE_SIZE)),
+        }
+    }
+}
+
+/// Creation and runtime code of one contract.
+pub struct ContractCode {
+    pub name: Option<String>,
+    pub init_code: Option<Vec<u8>>,
+    pub runtime: RuntimeCode,
+}
+
+/// The contracts to measure: the supplied artifact or bytecode, else every contract the
+/// source compiles to (if solc is available).
+pub fn contracts_for_job(source: &str, bytecode: Option<&BytecodeInput>, evm_version: Option<&str>) -> Result<(Vec<ContractCode>, bool), String> {
+    if let Some(input) = bytecode {
+        let contract = ContractCode { name: input.contract_name().map(str::to_string), init_code: input.init_code(), runtime: input.runtime_code()? };
+        return Ok((vec![contract], false));
+    }
+    if source.trim().is_empty() {
+        return Ok((Vec::new(), false));
+    }
+    compile(source, evm_version).map(|contracts| (contracts, true))
+}
+
+/// Compiles the source with solc's standard JSON interface.
+fn compile(source: &str, evm_version: Option<&str>) -> Result<Vec<ContractCode>, String> {
+    let mut settings = json!({
+        "optimizer": { "enabled": true, "runs": OPTIMIZER_RUNS },
+        "outputSelection": { "*": { "*": ["evm.bytecode.object", "evm.deployedBytecode.object", "evm.deployedBytecode.sourceMap"] } },
+    });
+    if let Some(fork) = evm_version.filter(|v| EvmFork::parse(v).is_some()) {
+        settings["evmVersion"] = json!(fork.trim().to_lowercase());
+    }
+    let input = json!({ "language": "Solidity", "sources": { "contract.sol": { "content": source } }, "settings": settings });
+
+    let solc = env::var(SOLC_ENV_VAR).unwrap_or_else(|_| "solc".to_string());
+    let mut child = Command::new(&solc)
+        .arg("--standard-json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to run {}: {}", solc, e))?;
+    // Feed and drain the pipes on their own threads so a large input or output cannot stall the timeout loop.
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_string();
+    thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = thread::spawn(move || {
+        let mut buffer = Vec::new();
+        stdout.read_to_end(&mut buffer).map(|_| buffer)
+    });
+
+    let deadline = Instant::now() + SOLC_TIMEOUT;
+    loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(_) => break,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("solc did not finish within {} seconds and was stopped", SOLC_TIMEOUT.as_secs()));
+            }
+            None => thread::sleep(Duration::from_millis(50)),
+        }
+    }
+    let stdout = reader.join().map_err(|_| "failed to read solc output".to_string())?.map_err(|e| e.to_string())?;
+    let output: Value = serde_json::from_slice(&stdout).map_err(|e| format!("unreadable solc output: {}", e))?;
+
+    let errors: Vec<&str> = output["errors"].as_array().into_iter().flatten()
+        .filter(|e| e["severity"] == "error")
+        .filter_map(|e| e["formattedMessage"].as_str())
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+
+    let mut contracts = Vec::new();
+    for (name, contract) in output["contracts"]["contract.sol"].as_object().into_iter().flatten() {
+        let runtime = contract.pointer("/evm/deployedBytecode/object").and_then(Value::as_str).and_then(sentinel_common::decode_hex).unwrap_or_default();
+        // Interfaces and abstract contracts have no code.
+        if runtime.is_empty() {
+            continue;
+        }
+        let source_map = contract.pointer("/evm/deployedBytecode/sourceMap").and_then(Value::as_str);
+        contracts.push(ContractCode {
+            name: Some(name.clone()),
+            init_code: contract.pointer("/evm/bytecode/object").and_then(Value::as_str).and_then(sentinel_common::decode_hex),
+            runtime: RuntimeCode::new(&runtime, source_map, Some(0))?,
+        });
+    }
+    Ok(contracts)
+}
+
+/// Reports contracts whose runtime code or initcode exceeds, or nearly reaches, the target's
+/// limits, naming the functions that contribute the most code.
+pub fn report_code_size(source: &str, contracts: &[ContractCode], compiled: bool, limits: &CodeSizeLimits, issues: &mut Vec<PortabilityIssue>) {
+    let masked = mask_comments_and_strings(source);
+    let outline = SourceOutline::parse(source);
+    let build = if compiled { format!(" (compiled with the optimizer at {} runs)", OPTIMIZER_RUNS) } else { String::new() };
+
+    for contract in contracts {
+        let label = contract.name.as_deref().map(|n| format!("`{}`", n)).unwrap_or_else(|| "The contract".to_string());
+        let line = contract.name.as_deref().and_then(|n| declaration_line(&masked, n)).unwrap_or(0);
+        let largest = largest_functions(&contract.runtime, source, &outline);
+        let contributors = if largest.is_empty() {
+            String::new()
+        } else {
+            let named: Vec<String> = largest.iter().map(|(name, size)| format!("`{}` (~{} bytes)", name, size)).collect();
+            format!(" The largest functions are {}.", named.join(", "))
+        };
+
+        let runtime_size = contract.runtime.code.len();
+        if let Some(severity) = severity_for(runtime_size, limits.runtime) {
+            let outcome = if severity == Severity::Critical {
+                format!("above the target's limit of {} bytes (EIP-170), so deploying it reverts", limits.runtime)
+            } else {
+                format!("close to the target's limit of {} bytes (EIP-170)", limits.runtime)
+            };
+            issues.push(PortabilityIssue {
+                line,
+                issue_type: "Contract Size Limit".to_string(),
+                severity,
+                description: format!("{} has {} bytes of runtime code{}, {}.{}", label, runtime_size, build, outcome, contributors),
+                recommendation: "Move the largest functions into external libraries or separate contracts (e.g. a diamond or proxy split), lower the optimizer `runs` setting, or try `viaIR`.".to_string(),
+            });
+        }
+
+        let (Some(init_code), Some(limit)) = (&contract.init_code, limits.init_code) else { continue };
+        if let Some(severity) = severity_for(init_code.len(), limit) {
+            let outcome = if severity == Severity::Critical {
+                format!("above the target's limit of {} bytes (EIP-3860), so the creation transaction fails", limit)
+            } else {
+                format!("close to the target's limit of {} bytes (EIP-3860) before constructor arguments are appended", limit)
+            };
+            issues.push(PortabilityIssue {
+                line,
+                issue_type: "Initcode Size Limit".to_string(),
+                severity,
+                description: format!("{} has {} bytes of initcode{}, {}.", label, init_code.len(), build, outcome),
+                recommendation: "Shrink the runtime code and constructor, or deploy large immutable data separately (e.g. with SSTORE2) instead of embedding it.".to_string(),
+            });
+        }
+    }
+}
+
+fn severity_for(size: usize, limit: usize) -> Option<Severity> {
+    if size > limit {
+        Some(Severity::Critical)
+    } else if size * 100 >= limit * NEAR_LIMIT_PERCENT {
+        Some(Severity::Low)
+    } else {
+        None
+    }
+}
+
+fn declaration_line(masked: &str, name: &str) -> Option<u32> {
+    let pattern = format!(r"\b(?:contract|library)\s+{}\b", regex::escape(name));
+    Regex::new(&pattern).unwrap().find(masked).map(|m| line_at(masked, m.start()))
+}
+
+/// Runtime bytes attributed to each source function through the source map, largest first.
+fn largest_functions(runtime: &RuntimeCode, source: &str, outline: &SourceOutline) -> Vec<(String, usize)> {
+    let mut sizes: HashMap<String, usize> = HashMap::new();
+    for (index, instruction) in evm::disassemble(evm::strip_metadata(&runtime.code)).iter().enumerate() {
+        let Some(offset) = runtime.source_offset(index, source) else { continue };
+        let Some(function) = outline.function_at_line(line_at(source, offset)) else { continue };
+        *sizes.entry(function.name.clone()).or_default() += 1 + instruction.immediate.len();
+    }
+    let mut sizes: Vec<(String, usize)> = sizes.into_iter().collect();
+    sizes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sizes.truncate(3);
+    sizes
+}
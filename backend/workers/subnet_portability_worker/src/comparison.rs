@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+
+use crate::genesis::{Genesis, UpgradeConfig};
+use crate::native_token::NativeTokenProfile;
+use crate::target::TargetProfile;
+use crate::{PortabilityIssue, Severity};
+
+/// One chain in a multi-target job. It replaces the job's own chain fields entirely, so a
+/// target without a genesis (e.g. the C-Chain) is analysed without Subnet context.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NamedTarget {
+    pub name: String,
+    pub subnet_genesis: Option<Genesis>,
+    pub upgrade_config: Option<UpgradeConfig>,
+    pub target_network: Option<String>,
+    pub target_profile: Option<TargetProfile>,
+    pub native_token: Option<NativeTokenProfile>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Deployable,
+    Degraded,
+    WillRevert,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TargetVerdict {
+    pub name: String,
+    pub verdict: Verdict,
+    // Issue types behind a `will_revert` or `degraded` verdict.
+    pub reasons: Vec<String>,
+    pub issue_count: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TargetSeverity {
+    pub target: String,
+    pub severity: Severity,
+}
+
+/// A finding (an issue type and description on a line) and the targets it applies to. The
+/// recommendation is that of its most severe instance.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ComparedFinding {
+    pub line: u32,
+    pub issue_type: String,
+    pub description: String,
+    pub recommendation: String,
+    pub applies_to: Vec<TargetSeverity>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TargetComparison {
+    pub targets: Vec<TargetVerdict>,
+    pub findings: Vec<ComparedFinding>,
+}
+
+/// Builds the finding-by-target matrix and per-target verdicts from each target's issues.
+/// Also returns one issue per finding, at its highest severity and naming the targets it
+/// applies to, as the job's flat output.
+pub fn compare(results: Vec<(String, Vec<PortabilityIssue>)>) -> (Vec<PortabilityIssue>, TargetComparison) {
+    let mut findings: Vec<(PortabilityIssue, Vec<TargetSeverity>)> = Vec::new();
+    let mut targets = Vec::new();
+
+    for (name, issues) in &results {
+        targets.push(verdict_for(name, issues));
+        for issue in issues {
+            let applies = TargetSeverity { target: name.clone(), severity: issue.severity };
+            // Several findings can share a line and type, e.g. bytecode findings at line 0 or one
+            // line calling two precompiles, so the description is part of their identity.
+            match findings.iter_mut().find(|(f, _)| f.line == issue.line && f.issue_type == issue.issue_type && f.description == issue.description) {
+                Some((worst, applies_to)) => {
+                    // A finding can be reported twice for one target; count the target once.
+                    match applies_to.iter_mut().find(|a| a.target == *name) {
+                        Some(existing) => existing.severity = existing.severity.max(issue.severity),
+                        None => applies_to.push(applies),
+                    }
+                    if issue.severity > worst.severity {
+                        *worst = issue.clone();
+                    }
+                }
+                None => findings.push((issue.clone(), vec![applies])),
+            }
+        }
+    }
+    findings.sort_by(|(a, _), (b, _)| b.severity.cmp(&a.severity).then(a.line.cmp(&b.line)).then_with(|| a.issue_type.cmp(&b.issue_type)));
+
+    let output = findings.iter()
+        .map(|(worst, applies_to)| {
+            let names: Vec<&str> = applies_to.iter().map(|a| a.target.as_str()).collect();
+            let scope = if names.len() == results.len() { "all targets".to_string() } else { names.join(", ") };
+            PortabilityIssue { description: format!("{} [Applies to: {}]", worst.description, scope), ..worst.clone() }
+        })
+        .collect();
+    let findings = findings.into_iter()
+        .map(|(worst, applies_to)| ComparedFinding { line: worst.line, issue_type: worst.issue_type, description: worst.description, recommendation: worst.recommendation, applies_to })
+        .collect();
+    (output, TargetComparison { targets, findings })
+}
+
+/// Critical findings make deployment or core calls revert; High and Medium ones leave the
+/// contract working with degraded guarantees.
+fn verdict_for(name: &str, issues: &[PortabilityIssue]) -> TargetVerdict {
+    let worst = issues.iter().map(|i| i.severity).max();
+    let verdict = match worst {
+        Some(Severity::Critical) => Verdict::WillRevert,
+        Some(Severity::High | Severity::Medium) => Verdict::Degraded,
+        Some(Severity::Low) | None => Verdict::Deployable,
+    };
+    let mut reasons: Vec<String> = Vec::new();
+    if verdict != Verdict::Deployable {
+        for issue in issues.iter().filter(|i| Some(i.severity) == worst) {
+            if !reasons.contains(&issue.issue_type) {
+                reasons.push(issue.issue_type.clone());
+            }
+        }
+    }
+    TargetVerdict { name: name.to_string(), verdict, reasons, issue_count: issues.len() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(line: u32, issue_type: &str, description: &str, severity: Severity) -> PortabilityIssue {
+        PortabilityIssue { line, issue_type: issue_type.to_string(), severity, description: description.to_string(), recommendation: String::new() }
+    }
+
+    #[test]
+    fn same_line_and_type_with_different_descriptions_stay_separate() {
+        let warp = "Contract interacts with the 'Warp' precompile, but it is NOT enabled in the provided Subnet genesis.";
+        let minter = "Contract interacts with the 'NativeMinter' precompile, but it is NOT enabled in the provided Subnet genesis.";
+        let (output, comparison) = compare(vec![
+            ("subnet-a".to_string(), vec![issue(7, "Precompile Mismatch", warp, Severity::Critical), issue(7, "Precompile Mismatch", minter, Severity::Critical)]),
+            ("subnet-b".to_string(), vec![issue(7, "Precompile Mismatch", minter, Severity::Critical)]),
+        ]);
+
+        assert_eq!(output.len(), 2);
+        let applies_to = |description: &str| {
+            let finding = comparison.findings.iter().find(|f| f.description == description).unwrap();
+            finding.applies_to.iter().map(|a| a.target.as_str()).collect::<Vec<_>>()
+        };
+        assert_eq!(applies_to(warp), ["subnet-a"]);
+        assert_eq!(applies_to(minter), ["subnet-a", "subnet-b"]);
+        assert!(output.iter().any(|i| i.description == format!("{} [Applies to: subnet-a]", warp)));
+        assert!(output.iter().any(|i| i.description == format!("{} [Applies to: all targets]", minter)));
+    }
+
+    #[test]
+    fn same_finding_keeps_its_worst_severity_per_target() {
+        let (output, comparison) = compare(vec![
+            ("a".to_string(), vec![issue(0, "Bytecode Chain ID", "x", Severity::Low)]),
+            ("b".to_string(), vec![issue(0, "Bytecode Chain ID", "x", Severity::High)]),
+        ]);
+        assert_eq!(output.len(), 1);
+        assert_eq!(output[0].severity, Severity::High);
+        assert_eq!(comparison.targets.iter().map(|t| t.verdict).collect::<Vec<_>>(), [Verdict::Deployable, Verdict::Degraded]);
+    }
+}
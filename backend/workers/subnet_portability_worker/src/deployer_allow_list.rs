@@ -0,0 +1,112 @@
+use regex::Regex;
+
+use sentinel_common::solidity::{assembly_blocks, line_at, mask_comments_and_strings};
+
+use crate::genesis::{self, Genesis, PrecompileStatus, UpgradeConfig};
+use crate::{PortabilityIssue, Severity};
+
+// Runtime code prefix of an EIP-1167 minimal proxy, up to the implementation address.
+const EIP1167_PREFIX: &str = "363d3d373d3d3d363d73";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FactoryKind {
+    New,
+    NewWithSalt,
+    Clone,
+    AssemblyCreate,
+    AssemblyCreate2,
+    BytecodeCreate,
+    BytecodeCreate2,
+}
+
+impl FactoryKind {
+    fn describe(self) -> &'static str {
+        match self {
+            FactoryKind::New => "`new` contract creation (CREATE)",
+            FactoryKind::NewWithSalt => "salted `new` contract creation (CREATE2)",
+            FactoryKind::Clone => "an EIP-1167 minimal proxy clone",
+            FactoryKind::AssemblyCreate => "an inline assembly `create`",
+            FactoryKind::AssemblyCreate2 => "an inline assembly `create2`",
+            FactoryKind::BytecodeCreate => "a `CREATE` instruction in its bytecode",
+            FactoryKind::BytecodeCreate2 => "a `CREATE2` instruction in its bytecode",
+        }
+    }
+}
+
+/// Finds every place the contract deploys other contracts and, when the target Subnet restricts
+/// deployments with the Contract Deployer Allow List, reports that the account sending the
+/// transaction needs to be allow-listed. The precompile checks `tx.origin`, not the contract
+/// executing CREATE or CREATE2.
+pub fn report_factory_patterns(source: &str, genesis: Option<&Genesis>, upgrade_config: Option<&UpgradeConfig>, deployment_timestamp: u64, issues: &mut Vec<PortabilityIssue>) {
+    report_factory_sites(&find_factory_sites(source), genesis, upgrade_config, deployment_timestamp, issues);
+}
+
+/// Reports factory sites found by any front end (source scan or bytecode) against the allow list.
+pub fn report_factory_sites(sites: &[(u32, FactoryKind)], genesis: Option<&Genesis>, upgrade_config: Option<&UpgradeConfig>, deployment_timestamp: u64, issues: &mut Vec<PortabilityIssue>) {
+    let events = genesis::precompile_events(genesis, upgrade_config, "contractDeployerAllowListConfig");
+    let status = genesis::precompile_status(&events, deployment_timestamp);
+    let enforced_from = match status {
+        PrecompileStatus::Active { .. } => None,
+        PrecompileStatus::NotYetActive { activates_at } => Some(activates_at),
+        PrecompileStatus::NotConfigured | PrecompileStatus::Disabled { .. } => return,
+    };
+    let listed = genesis::allow_list_addresses(genesis, "contractDeployerAllowListConfig");
+
+    for (line, kind) in sites.iter().copied() {
+        let mut description = format!("The contract deploys other contracts via {}. ", kind.describe());
+        description.push_str(&match enforced_from {
+            None => "The target Subnet enables the Contract Deployer Allow List, so CREATE and CREATE2 revert unless the EOA that sends the transaction (`tx.origin`) is allow-listed as Enabled, Manager or Admin.".to_string(),
+            Some(ts) => format!("The target Subnet enables the Contract Deployer Allow List at timestamp {}; from then on CREATE and CREATE2 revert unless the EOA that sends the transaction (`tx.origin`) is allow-listed as Enabled, Manager or Admin.", ts),
+        });
+        if !listed.is_empty() {
+            description.push_str(&format!(" The genesis currently allow-lists {}.", listed.join(", ")));
+        }
+        issues.push(PortabilityIssue {
+            line,
+            issue_type: "Deployer Allow List Restriction".to_string(),
+            severity: if enforced_from.is_none() { Severity::High } else { Severity::Medium },
+            description,
+            recommendation: "The allow list checks the transaction's origin, not the contract running CREATE or CREATE2, so allow-listing this contract has no effect. Have an allow-list admin or manager call `setEnabled(<account>)` on the Contract Deployer Allow List (0x0200000000000000000000000000000000000000) for every EOA that sends transactions reaching the factory, or pre-deploy the child contracts from an allow-listed account.".to_string(),
+        });
+    }
+}
+
+fn find_factory_sites(source: &str) -> Vec<(u32, FactoryKind)> {
+    // `new Foo(...)` and `new Foo{salt: s}(...)`; `new bytes(n)`, `new string(n)` and `new T[](n)` only allocate memory.
+    let new_regex = Regex::new(r"\bnew\s+([A-Za-z_$][\w$.]*)\s*(\{[^}]*\})?\s*\(").unwrap();
+    let clone_regex = Regex::new(r"\b(Clones|LibClone|ClonesUpgradeable)\s*\.\s*(clone|cloneDeterministic)\w*\s*\(").unwrap();
+    let create_regex = Regex::new(r"\b(create2?)\s*\(").unwrap();
+
+    let masked = mask_comments_and_strings(source);
+    let mut sites: Vec<(u32, FactoryKind)> = Vec::new();
+    let mut push = |line, kind| {
+        if !sites.contains(&(line, kind)) {
+            sites.push((line, kind));
+        }
+    };
+
+    for c in new_regex.captures_iter(&masked) {
+        if matches!(&c[1], "bytes" | "string") {
+            continue;
+        }
+        let salted = c.get(2).is_some_and(|opts| opts.as_str().contains("salt"));
+        push(line_at(&masked, c.get(0).unwrap().start()), if salted { FactoryKind::NewWithSalt } else { FactoryKind::New });
+    }
+    for m in clone_regex.find_iter(&masked) {
+        push(line_at(&masked, m.start()), FactoryKind::Clone);
+    }
+    // The proxy bytecode usually sits in a `hex"..."` literal, which masking blanks out.
+    for (i, line) in source.lines().enumerate() {
+        if line.to_lowercase().contains(EIP1167_PREFIX) {
+            push((i + 1) as u32, FactoryKind::Clone);
+        }
+    }
+    for block in assembly_blocks(&masked) {
+        for c in create_regex.captures_iter(&masked[block.clone()]) {
+            let kind = if &c[1] == "create2" { FactoryKind::AssemblyCreate2 } else { FactoryKind::AssemblyCreate };
+            push(line_at(&masked, block.start + c.get(0).unwrap().start()), kind);
+        }
+    }
+    sites.sort_by_key(|(line, _)| *line);
+    sites
+}
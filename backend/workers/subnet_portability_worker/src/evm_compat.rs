@@ -0,0 +1,195 @@
+use regex::Regex;
+
+use sentinel_common::evm::{opcode_by_name, EvmFork, Opcode};
+use sentinel_common::solidity::{assembly_blocks, line_at, mask_comments_and_strings, pragma_range};
+
+use crate::genesis::{self, Genesis, UpgradeConfig};
+use crate::{PortabilityIssue, Severity};
+
+// Solidity features that compile to a specific opcode, as (pattern, opcode, description).
+const SOURCE_FEATURES: &[(&str, &str, &str)] = &[
+    (r"\btransient\b", "TSTORE", "a `transient` storage variable"),
+    (r"\bblock\s*\.\s*basefee\b", "BASEFEE", "`block.basefee`"),
+    (r"\bblock\s*\.\s*blobbasefee\b", "BLOBBASEFEE", "`block.blobbasefee`"),
+    (r"\bblobhash\s*\(", "BLOBHASH", "`blobhash()`"),
+    (r"\bblock\s*\.\s*chainid\b", "CHAINID", "`block.chainid`"),
+    (r"\baddress\s*\(\s*this\s*\)\s*\.\s*balance\b", "SELFBALANCE", "`address(this).balance`"),
+];
+
+/// The EVM level of the target chain at deployment time, from its network upgrade timestamps.
+/// Only built when the job sets at least one of them.
+pub struct TargetEvm {
+    pub active: EvmFork,
+    pub upcoming: Vec<(u64, EvmFork)>,
+    pub deployment_timestamp: u64,
+}
+
+impl TargetEvm {
+    pub fn from_job(genesis: Option<&Genesis>, upgrade_config: Option<&UpgradeConfig>, deployment_timestamp: u64) -> Option<Self> {
+        let schedule = genesis::evm_fork_schedule(genesis?, upgrade_config)?;
+        let (active, upcoming) = genesis::evm_fork_at(&schedule, deployment_timestamp);
+        Some(TargetEvm { active, upcoming, deployment_timestamp })
+    }
+
+    /// `None` if the fork is active at deployment; otherwise when (if ever) it activates.
+    pub fn availability(&self, required: EvmFork) -> Option<Option<u64>> {
+        if required <= self.active {
+            return None;
+        }
+        Some(self.upcoming.iter().find(|(_, fork)| *fork >= required).map(|(ts, _)| *ts))
+    }
+
+    /// Severity and explanation for something that needs `required`, or `None` if it is supported.
+    pub fn assess(&self, required: EvmFork) -> Option<(Severity, String)> {
+        match self.availability(required)? {
+            Some(ts) => Some((Severity::High, format!("The target only reaches the {} level ({}) at timestamp {}, after the deployment time of {}.", required.name(), upgrade_name(required), ts, self.deployment_timestamp))),
+            None => Some((Severity::Critical, format!("The target runs the {} EVM level and has no upgrade to {} ({}) scheduled.", self.active.name(), required.name(), upgrade_name(required)))),
+        }
+    }
+}
+
+/// Compares the opcodes a contract needs, from its source features and its compiler target
+/// (`evmVersion`, or the solc default implied by the pragma), with the target's EVM level.
+pub fn report_evm_compatibility(source: &str, target: &TargetEvm, evm_version: Option<&str>, issues: &mut Vec<PortabilityIssue>) {
+    let masked = mask_comments_and_strings(source);
+
+    for (opcode, lines, feature) in required_opcodes(&masked) {
+        let Some((severity, reason)) = target.assess(opcode.since) else { continue };
+        let others = if lines.len() > 1 { format!(" (also used on line{} {})", if lines.len() > 2 { "s" } else { "" }, lines[1..].iter().map(|l| l.to_string()).collect::<Vec<_>>().join(", ")) } else { String::new() };
+        issues.push(PortabilityIssue {
+            line: lines[0],
+            issue_type: "Unsupported Opcode".to_string(),
+            severity,
+            description: format!("{} compiles to `{}`, introduced in {}{}. {}", capitalize(&feature), opcode.name(), opcode.since.name(), others, reason),
+            recommendation: format!("Avoid {} on this Subnet, or deploy once it activates {}.", feature, upgrade_name(opcode.since)),
+        });
+    }
+
+    report_compiler_target(&masked, target, evm_version, issues);
+}
+
+/// Each opcode above Frontier the source needs, with the lines it appears on and the feature that needs it.
+fn required_opcodes(masked: &str) -> Vec<(Opcode, Vec<u32>, String)> {
+    let mut found: Vec<(Opcode, Vec<u32>, String)> = Vec::new();
+    let mut record = |opcode: Opcode, line: u32, feature: String| match found.iter_mut().find(|(op, _, _)| *op == opcode) {
+        Some((_, lines, _)) if !lines.contains(&line) => lines.push(line),
+        Some(_) => {}
+        None => found.push((opcode, vec![line], feature)),
+    };
+
+    for (pattern, name, feature) in SOURCE_FEATURES {
+        let opcode = opcode_by_name(name).expect("source features name real opcodes");
+        for m in Regex::new(pattern).unwrap().find_iter(masked) {
+            record(opcode, line_at(masked, m.start()), feature.to_string());
+        }
+    }
+
+    // Yul builtins are named after the opcodes they emit.
+    let builtin_regex = Regex::new(r"\b([a-z][a-z0-9]*)\s*\(").unwrap();
+    for block in assembly_blocks(masked) {
+        for c in builtin_regex.captures_iter(&masked[block.clone()]) {
+            let Some(opcode) = opcode_by_name(&c[1]).filter(|op| op.since > EvmFork::Frontier) else { continue };
+            record(opcode, line_at(masked, block.start + c.get(0).unwrap().start()), format!("the assembly builtin `{}()`", &c[1]));
+        }
+    }
+
+    found.sort_by_key(|(_, lines, _)| lines[0]);
+    found
+}
+
+fn report_compiler_target(masked: &str, target: &TargetEvm, evm_version: Option<&str>, issues: &mut Vec<PortabilityIssue>) {
+    let pragma = Regex::new(r"\bpragma\s+solidity\s+([^;]+);").unwrap().captures(masked).map(|c| (line_at(masked, c.get(0).unwrap().start()), c[1].to_string()));
+    let pin = format!("Set `evmVersion` to \"{}\" in the compiler settings (foundry.toml, hardhat.config), or deploy after the target activates the required upgrade.", target.active.name());
+
+    if let Some(version) = evm_version {
+        let Some(fork) = EvmFork::parse(version) else {
+            eprintln!("Ignoring unknown evmVersion '{}'", version);
+            return;
+        };
+        let Some((severity, reason)) = target.assess(fork) else { return };
+        issues.push(PortabilityIssue {
+            line: pragma.as_ref().map(|(line, _)| *line).unwrap_or(0),
+            issue_type: "EVM Version Mismatch".to_string(),
+            severity,
+            description: format!("The contract is compiled with evmVersion \"{}\"{}. {}", version, codegen_note(fork, target.active), reason),
+            recommendation: pin,
+        });
+        return;
+    }
+
+    let Some((line, constraint)) = pragma else { return };
+    let Some((lowest, highest)) = pragma_range(&constraint) else { return };
+    let oldest_default = EvmFork::solc_default(lowest);
+    let newest_default = EvmFork::solc_default(highest);
+    if let Some((severity, reason)) = target.assess(oldest_default) {
+        issues.push(PortabilityIssue {
+            line,
+            issue_type: "EVM Version Mismatch".to_string(),
+            severity,
+            description: format!("Every compiler allowed by `pragma solidity {}` targets {} by default{}. {}", constraint.trim(), oldest_default.name(), codegen_note(oldest_default, target.active), reason),
+            recommendation: pin,
+        });
+    } else if target.availability(newest_default).is_some() {
+        issues.push(PortabilityIssue {
+            line,
+            issue_type: "EVM Version Mismatch".to_string(),
+            severity: Severity::Medium,
+            description: format!("`pragma solidity {}` allows compilers that target {} by default{}, above the target's {} level.", constraint.trim(), newest_default.name(), codegen_note(newest_default, target.active), target.active.name()),
+            recommendation: pin,
+        });
+    }
+}
+
+/// Opcodes solc emits on its own when targeting `fork`, beyond what `active` supports.
+fn codegen_note(fork: EvmFork, active: EvmFork) -> String {
+    let emitted: Vec<&str> = [("PUSH0", EvmFork::Shanghai), ("MCOPY", EvmFork::Cancun)]
+        .iter()
+        .filter(|(_, since)| *since <= fork && *since > active)
+        .map(|(name, _)| *name)
+        .collect();
+    if emitted.is_empty() { String::new() } else { format!(", so the generated code uses {}", emitted.join(" and ")) }
+}
+
+// Features are written to follow a verb; as sentence subjects they need a capital letter.
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+fn upgrade_name(fork: EvmFork) -> &'static str {
+    match fork {
+        EvmFork::Cancun => "Etna",
+        EvmFork::Shanghai => "Durango",
+        _ => "an earlier network upgrade",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn genesis(config: serde_json::Value) -> Genesis {
+        serde_json::from_value(json!({ "config": config })).unwrap()
+    }
+
+    #[test]
+    fn genesis_without_upgrade_timestamps_has_no_known_evm_level() {
+        assert!(TargetEvm::from_job(Some(&genesis(json!({ "feeConfig": {} }))), None, 0).is_none());
+        assert!(TargetEvm::from_job(None, None, 0).is_none());
+    }
+
+    #[test]
+    fn known_schedule_reports_missing_and_pending_upgrades() {
+        let source = "pragma solidity ^0.8.24;\ncontract A {\n    uint256 transient locked;\n}\n";
+        let severity = |config| {
+            let target = TargetEvm::from_job(Some(&genesis(config)), None, 100).unwrap();
+            let mut issues = Vec::new();
+            report_evm_compatibility(source, &target, Some("shanghai"), &mut issues);
+            issues.iter().find(|i| i.issue_type == "Unsupported Opcode").map(|i| i.severity)
+        };
+        assert_eq!(severity(json!({ "feeConfig": {}, "durangoTimestamp": 0 })), Some(Severity::Critical));
+        assert_eq!(severity(json!({ "feeConfig": {}, "durangoTimestamp": 0, "etnaTimestamp": 200 })), Some(Severity::High));
+        assert_eq!(severity(json!({ "feeConfig": {}, "durangoTimestamp": 0, "etnaTimestamp": 100 })), None);
+    }
+}
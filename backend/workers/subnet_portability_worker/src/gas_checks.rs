@@ -0,0 +1,220 @@
+use regex::Regex;
+
+use sentinel_common::gas::GasSchedule;
+use sentinel_common::solidity::{assembly_blocks, line_at, mask_comments_and_strings, matching_close, SourceOutline};
+
+use crate::{PortabilityIssue, Severity};
+
+/// A call that forwards a fixed amount of gas.
+struct FixedGasCall {
+    line: u32,
+    // e.g. "`.call`" or "`.transfer`".
+    form: String,
+    // The gas expression as written; `None` for the implicit 2300 stipend.
+    expression: Option<String>,
+    gas: u64,
+}
+
+/// Reports every call that forwards a fixed amount of gas (call options on any call form,
+/// `.transfer`/`.send`, Yul calls with a literal gas argument) and every `gasleft()` threshold,
+/// comparing the amounts with the target Subnet's gas schedule and block gas limit.
+pub fn report_hardcoded_gas(source: &str, schedule: &GasSchedule, block_gas_limit: Option<u64>, issues: &mut Vec<PortabilityIssue>) {
+    let masked = mask_comments_and_strings(source);
+    let outline = SourceOutline::parse(source);
+
+    for call in fixed_gas_calls(&masked, &outline, schedule) {
+        let unaffordable = schedule.unaffordable(call.gas);
+        let amount = match &call.expression {
+            Some(expr) if expr.trim().parse::<u64>().is_err() => format!("a fixed {} gas (`{}`)", call.gas, expr.trim()),
+            Some(_) => format!("a fixed {} gas", call.gas),
+            None => format!("only the {} gas stipend", call.gas),
+        };
+        let mut description = format!("{} forwards {}.", call.form, amount);
+        let mut severity = if unaffordable.is_empty() { Severity::Low } else { Severity::Medium };
+        if unaffordable.iter().any(|(op, _)| *op == "a cold storage read") {
+            severity = Severity::High;
+        }
+        if unaffordable.is_empty() {
+            description.push_str(" That covers common callee operations under the target's gas schedule, but breaks if the Subnet reprices opcodes.");
+        } else {
+            let ops: Vec<String> = unaffordable.iter().map(|(op, cost)| format!("{} ({} gas)", op, cost)).collect();
+            description.push_str(&format!(" Under the target's gas schedule that is not enough for {}.", ops.join(", ")));
+        }
+        if let Some(limit) = block_gas_limit.filter(|limit| call.gas > *limit) {
+            severity = Severity::High;
+            description.push_str(&format!(" It also exceeds the target's block gas limit of {}.", limit));
+        }
+        issues.push(PortabilityIssue {
+            line: call.line,
+            issue_type: if call.expression.is_none() { "Fixed Gas Stipend" } else { "Hardcoded Gas Amount" }.to_string(),
+            severity,
+            description,
+            recommendation: if call.expression.is_none() {
+                "Use `.call{value: amount}(\"\")` and check the result (with a reentrancy guard) instead of relying on the 2300 gas stipend.".to_string()
+            } else {
+                "Gas costs differ between Subnets and change with network upgrades. Forward all gas, or make the amount configurable per deployment.".to_string()
+            },
+        });
+    }
+
+    report_gasleft_thresholds(&masked, &outline, block_gas_limit, issues);
+}
+
+fn fixed_gas_calls(masked: &str, outline: &SourceOutline, schedule: &GasSchedule) -> Vec<FixedGasCall> {
+    let options_regex = Regex::new(r"\.\s*([A-Za-z_$][\w$]*)\s*\{").unwrap();
+    let gas_option_regex = Regex::new(r"(?:^|[,{\s])gas\s*:").unwrap();
+    let transfer_regex = Regex::new(r"\.\s*(transfer|send)\s*\(").unwrap();
+    let yul_call_regex = Regex::new(r"\b(call|staticcall|delegatecall|callcode)\s*\(").unwrap();
+    let mut calls = Vec::new();
+
+    // Call options, possibly spread over several lines: `addr.call{value: v, gas: 5000}(data)`.
+    for c in options_regex.captures_iter(masked) {
+        let open = c.get(0).unwrap().end();
+        let Some(close) = matching_close(masked, open - 1) else { continue };
+        if !masked[close + 1..].trim_start().starts_with('(') {
+            continue;
+        }
+        let options = &masked[open..close];
+        let Some(gas) = gas_option_regex.find(options) else { continue };
+        let expression = argument_at(options, gas.end());
+        let Some(value) = outline.resolve_uint(expression) else { continue };
+        calls.push(FixedGasCall {
+            line: line_at(masked, open + gas.start()),
+            form: match &c[1] {
+                method @ ("call" | "staticcall" | "delegatecall") => format!("`.{}`", method),
+                method => format!("The call to `.{}`", method),
+            },
+            expression: Some(expression.to_string()),
+            gas: value,
+        });
+    }
+
+    // `payable(to).transfer(amount)` and `.send(amount)` take a single argument, unlike ERC20 `transfer(to, amount)`.
+    for c in transfer_regex.captures_iter(masked) {
+        let open = c.get(0).unwrap().end();
+        let args = matching_close(masked, open - 1).map(|close| &masked[open..close]).unwrap_or_default();
+        if argument_at(args, 0).len() != args.trim().len() || args.trim().is_empty() {
+            continue;
+        }
+        calls.push(FixedGasCall { line: line_at(masked, open), form: format!("`.{}`", &c[1]), expression: None, gas: schedule.call_stipend });
+    }
+
+    for block in assembly_blocks(masked) {
+        for c in yul_call_regex.captures_iter(&masked[block.clone()]) {
+            let start = block.start + c.get(0).unwrap().end();
+            let expression = argument_at(masked, start);
+            let Some(value) = outline.resolve_uint(expression) else { continue };
+            calls.push(FixedGasCall {
+                line: line_at(masked, start),
+                form: format!("The assembly `{}`", &c[1]),
+                expression: Some(expression.to_string()),
+                gas: value,
+            });
+        }
+    }
+
+    calls.sort_by_key(|c| c.line);
+    calls
+}
+
+fn report_gasleft_thresholds(masked: &str, outline: &SourceOutline, block_gas_limit: Option<u64>, issues: &mut Vec<PortabilityIssue>) {
+    let gasleft_regex = Regex::new(r"\bgasleft\s*\(\s*\)").unwrap();
+    let after_regex = Regex::new(r"^(>=|<=|>|<|-)\s*([\w$.]+)").unwrap();
+    let before_regex = Regex::new(r"([\w$.]+)\s*(>=|<=|>|<)$").unwrap();
+
+    for m in gasleft_regex.find_iter(masked) {
+        let after = masked[m.end()..].trim_start();
+        let before = masked[..m.start()].trim_end();
+        let (operator, operand, gasleft_first) = match (after_regex.captures(after), before_regex.captures(before)) {
+            (Some(c), _) => (c[1].to_string(), c[2].to_string(), true),
+            (None, Some(c)) => (c[2].to_string(), c[1].to_string(), false),
+            (None, None) => continue,
+        };
+        let Some(threshold) = outline.resolve_uint(&operand) else { continue };
+
+        // `gasleft() > X` / `X < gasleft()` demand at least X gas; subtraction reserves X gas.
+        let demands_minimum = matches!((operator.as_str(), gasleft_first), (">" | ">=", true) | ("<" | "<=", false));
+        let mut description = match operator.as_str() {
+            "-" => format!("`gasleft()` reserves a fixed {} gas for the rest of the transaction.", threshold),
+            _ if demands_minimum => format!("The code requires `gasleft()` to stay above a fixed threshold of {} gas.", threshold),
+            _ => format!("The code branches on `gasleft()` against a fixed threshold of {} gas.", threshold),
+        };
+        let severity = match block_gas_limit {
+            Some(limit) if demands_minimum && threshold >= limit => {
+                description.push_str(&format!(" The target's block gas limit is {}, so this condition can never be met.", limit));
+                Severity::Critical
+            }
+            _ => {
+                description.push_str(" The amount of work it stands for depends on the target Subnet's opcode pricing.");
+                Severity::Low
+            }
+        };
+        issues.push(PortabilityIssue {
+            line: line_at(masked, m.start()),
+            issue_type: "Gas Threshold Assumption".to_string(),
+            severity,
+            description,
+            recommendation: "Derive gas thresholds from configuration rather than constants tuned for one chain's gas schedule.".to_string(),
+        });
+    }
+}
+
+/// The expression starting at `start` up to the next top-level `,` or closing bracket.
+fn argument_at(text: &str, start: usize) -> &str {
+    let mut depth = 0usize;
+    for (i, c) in text.bytes().enumerate().skip(start) {
+        match c {
+            b'(' | b'{' | b'[' => depth += 1,
+            b')' | b'}' | b']' if depth == 0 => return text[start..i].trim(),
+            b')' | b'}' | b']' => depth -= 1,
+            b',' if depth == 0 => return text[start..i].trim(),
+            _ => {}
+        }
+    }
+    text[start..].trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calls(source: &str) -> Vec<(u32, String, u64)> {
+        let masked = mask_comments_and_strings(source);
+        fixed_gas_calls(&masked, &SourceOutline::parse(source), &GasSchedule::builtin()).into_iter().map(|c| (c.line, c.form, c.gas)).collect()
+    }
+
+    #[test]
+    fn fixed_gas_in_every_call_form() {
+        let source = "contract A {\n    uint256 constant GAS = 50_000;\n    function f(address to, IToken token) external {\n        to.call{value: 1,\n            gas: GAS}(\"\");\n        token.deposit{gas: 3e4}();\n        payable(to).transfer(1);\n        token.transfer(to, 1);\n        assembly { let ok := call(2300, to, 0, 0, 0, 0, 0) }\n        to.call{value: 1}(\"\");\n    }\n}\n";
+        assert_eq!(calls(source), vec![
+            (5, "`.call`".to_string(), 50_000),
+            (6, "The call to `.deposit`".to_string(), 30_000),
+            (7, "`.transfer`".to_string(), 2300),
+            (9, "The assembly `call`".to_string(), 2300),
+        ]);
+    }
+
+    #[test]
+    fn gas_is_checked_against_the_block_gas_limit() {
+        let source = "contract A {\n    function f(address to) external {\n        to.call{gas: 20_000_000}(\"\");\n        require(gasleft() > 16_000_000);\n        if (gasleft() < 100_000) return;\n    }\n}\n";
+        let mut issues = Vec::new();
+        report_hardcoded_gas(source, &GasSchedule::builtin(), Some(15_000_000), &mut issues);
+        let found: Vec<(u32, &str, Severity)> = issues.iter().map(|i| (i.line, i.issue_type.as_str(), i.severity)).collect();
+        assert_eq!(found, vec![
+            (3, "Hardcoded Gas Amount", Severity::High),
+            (4, "Gas Threshold Assumption", Severity::Critical),
+            (5, "Gas Threshold Assumption", Severity::Low),
+        ]);
+    }
+
+    #[test]
+    fn stipend_covers_a_read_but_not_a_write() {
+        let source = "contract A {\n    function f(address payable to) external {\n        to.send(1);\n    }\n}\n";
+        let mut issues = Vec::new();
+        report_hardcoded_gas(source, &GasSchedule::builtin(), None, &mut issues);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].issue_type, "Fixed Gas Stipend");
+        assert_eq!(issues[0].severity, Severity::Medium);
+        assert!(issues[0].description.contains("a storage write") && !issues[0].description.contains("a cold storage read"));
+    }
+}
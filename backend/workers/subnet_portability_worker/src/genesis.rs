@@ -0,0 +1,228 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use sentinel_common::evm::EvmFork;
+
+// --- V3: Structs for parsing the subnet genesis file ---
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeConfig {
+    pub gas_limit: Option<u64>,
+    pub min_base_fee: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainConfig {
+    pub fee_config: FeeConfig,
+    pub precompile_validator_allow_list: Option<Map<String, Value>>,
+    pub upgrades: Option<UpgradeConfig>,
+    // Precompile configs (`txAllowListConfig`, `warpConfig`, ...) and network upgrade timestamps.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Genesis {
+    pub config: ChainConfig,
+    #[serde(default)]
+    pub alloc: HashMap<String, GenesisAccount>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisAccount {
+    pub balance: Option<String>,
+    pub code: Option<String>,
+}
+
+/// The chain's `upgrade.json`, or the `upgrades` block embedded in the genesis config.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpgradeConfig {
+    #[serde(default)]
+    pub precompile_upgrades: Vec<Map<String, Value>>,
+    // Network upgrade timestamps that replace the genesis ones, e.g. `durangoTimestamp`.
+    pub network_upgrade_overrides: Option<Map<String, Value>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PrecompileEvent {
+    pub timestamp: u64,
+    pub disable: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PrecompileStatus {
+    NotConfigured,
+    NotYetActive { activates_at: u64 },
+    Active { removal_at: Option<u64> },
+    Disabled { since: u64, reenabled_at: Option<u64> },
+}
+
+/// Collects every enable/disable event for a precompile config key (e.g. `txAllowListConfig`)
+/// from the genesis config, the genesis `upgrades` block and a standalone upgrade config.
+pub fn precompile_events(genesis: Option<&Genesis>, upgrade_config: Option<&UpgradeConfig>, config_key: &str) -> Vec<PrecompileEvent> {
+    let mut events = Vec::new();
+
+    if let Some(entry) = genesis.and_then(|g| g.config.extra.get(config_key)) {
+        events.extend(parse_event(entry));
+    }
+
+    let upgrade_entries = genesis
+        .and_then(|g| g.config.upgrades.as_ref())
+        .into_iter()
+        .chain(upgrade_config)
+        .flat_map(|u| u.precompile_upgrades.iter());
+    for upgrade in upgrade_entries {
+        if let Some(entry) = upgrade.get(config_key) {
+            events.extend(parse_event(entry));
+        }
+    }
+
+    events.sort_by_key(|e| e.timestamp);
+    events
+}
+
+fn parse_event(entry: &Value) -> Option<PrecompileEvent> {
+    let obj = entry.as_object()?;
+    Some(PrecompileEvent {
+        // Genesis precompile configs without a timestamp are active from block 0.
+        timestamp: obj.get("blockTimestamp").and_then(Value::as_u64).unwrap_or(0),
+        disable: obj.get("disable").and_then(Value::as_bool).unwrap_or(false),
+    })
+}
+
+/// Resolves whether a precompile is usable at `at` given its sorted enable/disable events.
+pub fn precompile_status(events: &[PrecompileEvent], at: u64) -> PrecompileStatus {
+    let last = events.iter().rev().find(|e| e.timestamp <= at);
+    let mut upcoming = events.iter().filter(|e| e.timestamp > at);
+
+    match last {
+        None => match upcoming.find(|e| !e.disable) {
+            Some(next) => PrecompileStatus::NotYetActive { activates_at: next.timestamp },
+            None => PrecompileStatus::NotConfigured,
+        },
+        Some(e) if e.disable => PrecompileStatus::Disabled {
+            since: e.timestamp,
+            reenabled_at: upcoming.find(|e| !e.disable).map(|e| e.timestamp),
+        },
+        Some(_) => PrecompileStatus::Active {
+            removal_at: upcoming.find(|e| e.disable).map(|e| e.timestamp),
+        },
+    }
+}
+
+/// Addresses given any role (admin, manager or enabled) by an allow-list precompile config in the genesis.
+pub fn allow_list_addresses(genesis: Option<&Genesis>, config_key: &str) -> Vec<String> {
+    let Some(config) = genesis.and_then(|g| g.config.extra.get(config_key)) else {
+        return Vec::new();
+    };
+    ["adminAddresses", "managerAddresses", "enabledAddresses"]
+        .iter()
+        .filter_map(|key| config.get(key).and_then(Value::as_array))
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect()
+}
+
+// Network upgrade timestamp keys (Subnet-EVM, then C-Chain) and the EVM level they unlock.
+const NETWORK_UPGRADES: &[(&str, EvmFork)] = &[
+    ("durangoTimestamp", EvmFork::Shanghai),
+    ("etnaTimestamp", EvmFork::Cancun),
+    ("apricotPhase3BlockTimestamp", EvmFork::London),
+    ("durangoBlockTimestamp", EvmFork::Shanghai),
+    ("cancunTime", EvmFork::Cancun),
+];
+
+/// Every Subnet-EVM genesis runs at least London-level opcodes.
+pub const BASE_EVM_FORK: EvmFork = EvmFork::London;
+
+/// The EVM levels unlocked by the network upgrade timestamps, sorted by activation time. `None`
+/// when neither the genesis nor the upgrade config sets any, so the fork level is unknown.
+pub fn evm_fork_schedule(genesis: &Genesis, upgrade_config: Option<&UpgradeConfig>) -> Option<Vec<(u64, EvmFork)>> {
+    let overrides = upgrade_config.and_then(|u| u.network_upgrade_overrides.as_ref());
+    let mut schedule: Vec<(u64, EvmFork)> = NETWORK_UPGRADES
+        .iter()
+        .filter_map(|(key, fork)| {
+            let timestamp = overrides.and_then(|o| o.get(*key)).or_else(|| genesis.config.extra.get(*key))?;
+            Some((timestamp.as_u64()?, *fork))
+        })
+        .collect();
+    schedule.sort();
+    (!schedule.is_empty()).then_some(schedule)
+}
+
+/// The EVM level active at `at`, and the upgrades still to come after it.
+pub fn evm_fork_at(schedule: &[(u64, EvmFork)], at: u64) -> (EvmFork, Vec<(u64, EvmFork)>) {
+    let active = schedule.iter().filter(|(ts, _)| *ts <= at).map(|(_, fork)| *fork).fold(BASE_EVM_FORK, EvmFork::max);
+    let upcoming = schedule.iter().filter(|(ts, fork)| *ts > at && *fork > active).copied().collect();
+    (active, upcoming)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn genesis(config: Value) -> Genesis {
+        serde_json::from_value(json!({ "config": config })).unwrap()
+    }
+
+    fn upgrades(upgrades: Value) -> UpgradeConfig {
+        serde_json::from_value(json!({ "precompileUpgrades": upgrades })).unwrap()
+    }
+
+    #[test]
+    fn genesis_config_without_timestamp_is_active_from_block_zero() {
+        let genesis = genesis(json!({ "feeConfig": {}, "txAllowListConfig": { "adminAddresses": [] } }));
+        let events = precompile_events(Some(&genesis), None, "txAllowListConfig");
+        assert_eq!(precompile_status(&events, 0), PrecompileStatus::Active { removal_at: None });
+        assert_eq!(precompile_status(&precompile_events(Some(&genesis), None, "warpConfig"), 0), PrecompileStatus::NotConfigured);
+    }
+
+    #[test]
+    fn enable_disable_and_reenable_schedule() {
+        let genesis = genesis(json!({
+            "feeConfig": {},
+            "upgrades": { "precompileUpgrades": [{ "txAllowListConfig": { "blockTimestamp": 100 } }] },
+        }));
+        let upgrade_config = upgrades(json!([
+            { "txAllowListConfig": { "blockTimestamp": 300 } },
+            { "txAllowListConfig": { "blockTimestamp": 200, "disable": true } },
+        ]));
+        let events = precompile_events(Some(&genesis), Some(&upgrade_config), "txAllowListConfig");
+        assert_eq!(events.iter().map(|e| (e.timestamp, e.disable)).collect::<Vec<_>>(), vec![(100, false), (200, true), (300, false)]);
+
+        assert_eq!(precompile_status(&events, 50), PrecompileStatus::NotYetActive { activates_at: 100 });
+        assert_eq!(precompile_status(&events, 150), PrecompileStatus::Active { removal_at: Some(200) });
+        assert_eq!(precompile_status(&events, 250), PrecompileStatus::Disabled { since: 200, reenabled_at: Some(300) });
+        assert_eq!(precompile_status(&events, 400), PrecompileStatus::Active { removal_at: None });
+    }
+
+    #[test]
+    fn events_take_effect_at_their_own_timestamp() {
+        let events = [PrecompileEvent { timestamp: 100, disable: false }, PrecompileEvent { timestamp: 200, disable: true }];
+        assert_eq!(precompile_status(&events, 99), PrecompileStatus::NotYetActive { activates_at: 100 });
+        assert_eq!(precompile_status(&events, 100), PrecompileStatus::Active { removal_at: Some(200) });
+        assert_eq!(precompile_status(&events, 200), PrecompileStatus::Disabled { since: 200, reenabled_at: None });
+    }
+
+    #[test]
+    fn fork_level_is_unknown_without_upgrade_timestamps() {
+        assert_eq!(evm_fork_schedule(&genesis(json!({ "feeConfig": {} })), None), None);
+
+        let schedule = evm_fork_schedule(&genesis(json!({ "feeConfig": {}, "durangoTimestamp": 0, "etnaTimestamp": 500 })), None).unwrap();
+        assert_eq!(evm_fork_at(&schedule, 499), (EvmFork::Shanghai, vec![(500, EvmFork::Cancun)]));
+        assert_eq!(evm_fork_at(&schedule, 500), (EvmFork::Cancun, vec![]));
+    }
+
+    #[test]
+    fn disabled_without_prior_enable_is_not_configured() {
+        let events = [PrecompileEvent { timestamp: 100, disable: true }];
+        assert_eq!(precompile_status(&events, 50), PrecompileStatus::NotConfigured);
+    }
+}
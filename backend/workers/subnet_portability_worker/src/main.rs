@@ -1,41 +1,74 @@
 use redis::{Commands, Client, Connection};
 use serde::{Deserialize, Serialize};
-use regex::Regex;
-use serde_json::Value;
-use std::collections::HashSet; // V3 FIX: Import HashSet for deduplication
+use std::collections::{HashMap, HashSet}; // V3 FIX: Import HashSet for deduplication
+use std::time::{SystemTime, UNIX_EPOCH};
+
+mod address_checks;
+mod bytecode;
+mod chain_id;
+mod code_size;
+mod comparison;
+mod deployer_allow_list;
+mod evm_compat;
+mod gas_checks;
+mod genesis;
+mod native_token;
+mod target;
+mod tx_allow_list;
+use address_checks::AddressCheckContext;
+use bytecode::{BytecodeContext, BytecodeInput};
+use code_size::{CodeSizeLimits, ContractCode};
+use comparison::{NamedTarget, TargetComparison};
+use evm_compat::TargetEvm;
+use genesis::{Genesis, PrecompileEvent, PrecompileStatus, UpgradeConfig};
+use native_token::{NativeTokenProfile, NativeTokenSemantics};
+use target::{TargetChain, TargetProfile};
+use sentinel_common::addresses::AddressRegistry;
+use sentinel_common::gas::GasSchedule;
+use sentinel_common::precompiles::PrecompileRegistry;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)] // V3 FIX: Add traits for HashSet
 struct PortabilityIssue {
     line: u32,
     issue_type: String,
+    severity: Severity,
     description: String,
     recommendation: String,
 }
 
-// --- V3: Structs for parsing the subnet genesis file (Unchanged) ---
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-struct FeeConfig {
-    gas_limit: Option<u64>,
-}
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-struct ChainConfig {
-    fee_config: FeeConfig,
-    precompile_validator_allow_list: Option<serde_json::Map<String, Value>>,
-}
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-struct Genesis {
-    config: ChainConfig,
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
 }
 
 // --- V3: The job payload (Unchanged) ---
 #[derive(Serialize, Deserialize, Debug)]
 struct AnalysisJob {
     job_id: String,
+    // May be empty when only `bytecode` is provided.
+    #[serde(default)]
     source_code: String,
     subnet_genesis: Option<Genesis>,
+    // The chain's upgrade.json (`precompileUpgrades`), if it is not embedded in the genesis.
+    upgrade_config: Option<UpgradeConfig>,
+    // Unix timestamp the contract will be deployed at. Defaults to now.
+    deployment_timestamp: Option<u64>,
+    // Name of the target network in the address registry (e.g. "fuji"). Addresses known on it are not flagged.
+    target_network: Option<String>,
+    // Deployment manifest and/or state snapshot of the target chain, checked alongside the genesis `alloc`.
+    target_profile: Option<TargetProfile>,
+    // Declared properties of the target chain's native token (symbol, valueless).
+    native_token: Option<NativeTokenProfile>,
+    // Runtime bytecode (hex) or a build artifact, for contracts analysed without (or alongside) source.
+    bytecode: Option<BytecodeInput>,
+    // The `evmVersion` from the compiler settings, if known; otherwise the pragma's solc default is assumed.
+    evm_version: Option<String>,
+    // Several named target chains to compare. Each replaces the chain fields above.
+    targets: Option<Vec<NamedTarget>>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -43,33 +76,41 @@ struct AnalysisResult {
     job_id: String,
     worker_name: String,
     output: Vec<PortabilityIssue>,
+    // Finding-by-target matrix and per-target verdicts, for jobs with `targets`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comparison: Option<TargetComparison>,
 }
 
-const CCHAIN_ONLY_ADDRESSES: &[(&str, &str)] = &[
-    ("0x9Ad6C38BE94206cA50bb0d90783181662f0Cfa10", "Trader Joe V1 Router"),
-    ("0x60aE616a2155Ee3d9A68541Ba4544862310933d4", "Trader Joe V2 Router"),
-    ("0xE54Ca86531e17Ef3616d22Ca28b0D458b6C89106", "Pangolin Router"),
-    ("0xd00ae08403B959254dbA1188b832b412A4461b95", "Benqi Lending Market (qiAVAX)"),
-    ("0x2b2C81e08f1Af8835a78Bb2A90AE924ACE0eA4be", "Aave V2 Lending Pool"),
-];
-
-const COMMON_PRECOMPILES: &[(&str, &str)] = &[
-    ("0x0100000000000000000000000000000000000000", "P-Chain Handler"),
-    ("0x0200000000000000000000000000000000000000", "Contract Deployer Allow List"),
-    ("0x0200000000000000000000000000000000000001", "Contract Native Minter"),
-    ("0x0200000000000000000000000000000000000002", "Fee Manager"),
-];
+// Registries loaded once at startup. The address registry is hot-reloaded between jobs.
+struct Registries {
+    precompiles: PrecompileRegistry,
+    addresses: AddressRegistry,
+    // The last address registry error, so a broken registry is logged once rather than per job.
+    address_error: Option<String>,
+}
 
 fn main() -> redis::RedisResult<()> {
     println!("Starting Subnet Portability Worker [V3]...");
     let redis_client = Client::open("redis://127.0.0.1/")?;
     let mut redis_con = redis_client.get_connection()?;
     println!("Successfully connected to Redis.");
-    listen_for_jobs(&mut redis_con);
+    let precompiles = PrecompileRegistry::load().unwrap_or_else(|e| {
+        eprintln!("{}. Falling back to the built-in precompile registry.", e);
+        PrecompileRegistry::builtin()
+    });
+    let (addresses, address_error) = match AddressRegistry::load() {
+        Ok(addresses) => (addresses, None),
+        Err(e) => {
+            eprintln!("{}. Continuing without known-address checks until the registry is fixed.", e);
+            (AddressRegistry::empty_at(AddressRegistry::configured_dir()), Some(e))
+        }
+    };
+    let mut registries = Registries { precompiles, addresses, address_error };
+    listen_for_jobs(&mut redis_con, &mut registries);
     Ok(())
 }
 
-fn listen_for_jobs(con: &mut Connection) {
+fn listen_for_jobs(con: &mut Connection, registries: &mut Registries) {
     let channel = "subnet_portability_jobs";
     println!("Listening for jobs on channel: '{}'", channel);
     loop {
@@ -82,7 +123,19 @@ fn listen_for_jobs(con: &mut Connection) {
                 match job {
                     Ok(parsed_job) => {
                         println!("Processing Job ID: {}", parsed_job.job_id);
-                        let result = analyze_portability_v3(&parsed_job);
+                        match registries.addresses.reload_if_changed() {
+                            Ok(true) => {
+                                println!("Address registry changed on disk, reloaded.");
+                                registries.address_error = None;
+                            }
+                            Ok(false) => {}
+                            Err(e) if registries.address_error.as_ref() == Some(&e) => {}
+                            Err(e) => {
+                                eprintln!("Failed to reload address registry, keeping the previous one: {}", e);
+                                registries.address_error = Some(e);
+                            }
+                        }
+                        let result = analyze_portability_v3(parsed_job, registries);
                         publish_result(con, result);
                     }
                     Err(e) => eprintln!("Error parsing job JSON: {}", e),
@@ -93,55 +146,139 @@ fn listen_for_jobs(con: &mut Connection) {
     }
 }
 
-fn analyze_portability_v3(job: &AnalysisJob) -> AnalysisResult {
+fn analyze_portability_v3(mut job: AnalysisJob, registries: &Registries) -> AnalysisResult {
+    let targets = job.targets.take().unwrap_or_default();
+    // Compiled once per job: only the size limits differ between targets.
+    let contracts = match code_size::contracts_for_job(&job.source_code, job.bytecode.as_ref(), job.evm_version.as_deref()) {
+        Ok(contracts) => Some(contracts),
+        Err(e) => {
+            eprintln!("Skipping code size check for Job ID {}: {}", job.job_id, e);
+            None
+        }
+    };
+    let (output, comparison) = if targets.is_empty() {
+        (find_portability_issues(&job, contracts.as_ref(), registries), None)
+    } else {
+        let mut results = Vec::new();
+        for target in targets {
+            job.subnet_genesis = target.subnet_genesis;
+            job.upgrade_config = target.upgrade_config;
+            job.target_network = target.target_network;
+            job.target_profile = target.target_profile;
+            job.native_token = target.native_token;
+            println!("Analyzing against target '{}'.", target.name);
+            results.push((target.name, find_portability_issues(&job, contracts.as_ref(), registries)));
+        }
+        let (output, comparison) = comparison::compare(results);
+        (output, Some(comparison))
+    };
+
+    AnalysisResult {
+        job_id: job.job_id.clone(),
+        worker_name: "SubnetPortabilityWorkerV3".to_string(),
+        output,
+        comparison,
+    }
+}
+
+fn find_portability_issues(job: &AnalysisJob, contracts: Option<&(Vec<ContractCode>, bool)>, registries: &Registries) -> Vec<PortabilityIssue> {
     let mut issues: Vec<PortabilityIssue> = Vec::new();
 
-    let chainid_regex = Regex::new(r"\bchainid\b").unwrap();
-    let msg_value_regex = Regex::new(r"\bmsg\.value\b").unwrap();
-    let balance_regex = Regex::new(r"\.balance\b").unwrap();
-    let hardcoded_gas_regex = Regex::new(r"\.call\s*\{\s*gas:").unwrap();
 
+    let target_chain = TargetChain::from_job(job.subnet_genesis.as_ref(), job.target_profile.as_ref());
     let subnet_gas_limit = job.subnet_genesis.as_ref().and_then(|g| g.config.fee_config.gas_limit);
-    let enabled_precompiles: Option<Vec<String>> = job.subnet_genesis.as_ref()
+    // `None` when the genesis has no legacy `precompileValidatorAllowList`.
+    let legacy_enabled_precompiles: Option<Vec<String>> = job.subnet_genesis.as_ref()
         .and_then(|g| g.config.precompile_validator_allow_list.as_ref())
         .map(|p| p.keys().cloned().collect());
-    
-    if subnet_gas_limit.is_some() || enabled_precompiles.is_some() {
+    let has_precompile_context = job.subnet_genesis.is_some() || job.upgrade_config.is_some();
+    let deployment_timestamp = job.deployment_timestamp.unwrap_or_else(|| {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+    });
+
+    // Enable/disable schedule per precompile address, from the genesis and upgrade configs.
+    // Precompiles without a config key (the Avalanche built-ins) can only be judged against the
+    // legacy list; without one, nothing in the genesis says whether they are available.
+    let precompile_statuses: HashMap<String, PrecompileStatus> = registries.precompiles.iter()
+        .filter(|p| !p.is_always_enabled())
+        .filter(|p| p.config_key.is_some() || legacy_enabled_precompiles.is_some())
+        .map(|p| {
+            let mut events = p.config_key.as_deref()
+                .map(|key| genesis::precompile_events(job.subnet_genesis.as_ref(), job.upgrade_config.as_ref(), key))
+                .unwrap_or_default();
+            if legacy_enabled_precompiles.iter().flatten().any(|addr| addr.eq_ignore_ascii_case(&p.address)) {
+                events.insert(0, PrecompileEvent { timestamp: 0, disable: false });
+            }
+            (p.address.to_lowercase(), genesis::precompile_status(&events, deployment_timestamp))
+        })
+        .collect();
+
+    if subnet_gas_limit.is_some() || has_precompile_context {
         println!("Analyzing with provided Subnet Genesis context.");
     }
 
-    for (i, line_content) in job.source_code.lines().enumerate() {
-        let line_num = (i + 1) as u32;
-
-        if chainid_regex.is_match(line_content) { issues.push(PortabilityIssue{line: line_num, issue_type: "Hardcoded Chain Assumption".to_string(), description: "The `chainid` opcode was used.".to_string(), recommendation: "Avoid using `chainid` for core logic. On a new Subnet, this value will be different and may break your contract.".to_string()}); }
-        if msg_value_regex.is_match(line_content) { issues.push(PortabilityIssue{line: line_num, issue_type: "Native Token Assumption".to_string(), description: "The `msg.value` keyword was used, assuming a native, value-bearing token.".to_string(), recommendation: "Be aware that many Subnets may use a valueless native token for gas, or may not use a native token at all (e.g., in favor of an ERC20 for fees). Logic relying on `msg.value > 0` may not be portable.".to_string()}); }
-        if balance_regex.is_match(line_content) { issues.push(PortabilityIssue{line: line_num, issue_type: "Native Token Assumption".to_string(), description: "The `.balance` property was used, assuming a native, value-bearing token.".to_string(), recommendation: "Similar to `msg.value`, be aware that the native token on a custom Subnet may not be AVAX and could have different properties. Logic checking `address.balance` might behave as expected.".to_string()}); }
-        if hardcoded_gas_regex.is_match(line_content) { issues.push(PortabilityIssue{line: line_num, issue_type: "Hardcoded Gas Amount".to_string(), description: "A low-level call with a hardcoded gas amount (`.call{gas: ...}`) was detected.".to_string(), recommendation: "This is a fragile pattern. Gas costs for opcodes can change, and Subnets may have different gas semantics. Avoid hardcoding gas unless absolutely necessary.".to_string()}); }
-        for (address, name) in CCHAIN_ONLY_ADDRESSES { if line_content.to_lowercase().contains(&address.to_lowercase()) { issues.push(PortabilityIssue{line: line_num, issue_type: "C-Chain Dependency".to_string(), description: format!("A hardcoded address for a known C-Chain protocol ({}) was found.", name), recommendation: "This contract will not exist on a new Subnet. Pass protocol addresses in the constructor or a setter function to make your contract portable.".to_string()}); }}
-
-        if let Some(ref precompiles) = enabled_precompiles {
-            for (addr, name) in COMMON_PRECOMPILES {
-                if line_content.to_lowercase().contains(&addr.to_lowercase()) {
-                    let is_enabled = precompiles.iter().any(|p| p.eq_ignore_ascii_case(addr));
-                    if !is_enabled {
-                        issues.push(PortabilityIssue {
-                            line: line_num,
-                            issue_type: "Precompile Mismatch".to_string(),
-                            description: format!("Contract interacts with the '{}' precompile, but it is NOT enabled in the provided Subnet genesis.", name),
-                            recommendation: "Ensure your target Subnet's genesis file enables all precompiles your contracts require.".to_string(),
-                        });
-                    }
-                }
+    let gas_schedule = match job.target_profile.as_ref().and_then(|p| p.gas_schedule.as_ref()) {
+        Some(overrides) => GasSchedule::with_overrides(overrides).unwrap_or_else(|e| {
+            eprintln!("Ignoring target gas schedule overrides: {}", e);
+            GasSchedule::builtin()
+        }),
+        None => GasSchedule::builtin(),
+    };
+    gas_checks::report_hardcoded_gas(&job.source_code, &gas_schedule, subnet_gas_limit, &mut issues);
+    deployer_allow_list::report_factory_patterns(&job.source_code, job.subnet_genesis.as_ref(), job.upgrade_config.as_ref(), deployment_timestamp, &mut issues);
+    tx_allow_list::report_tx_allow_list_impact(&job.source_code, job.subnet_genesis.as_ref(), job.upgrade_config.as_ref(), deployment_timestamp, &mut issues);
+    let target_evm = TargetEvm::from_job(job.subnet_genesis.as_ref(), job.upgrade_config.as_ref(), deployment_timestamp);
+    if let Some(target_evm) = &target_evm {
+        evm_compat::report_evm_compatibility(&job.source_code, target_evm, job.evm_version.as_deref(), &mut issues);
+    }
+    chain_id::report_chainid_usage(&job.source_code, &registries.addresses, &mut issues);
+
+    let native_token = NativeTokenSemantics::from_job(job.subnet_genesis.as_ref(), job.upgrade_config.as_ref(), job.native_token.as_ref(), deployment_timestamp);
+    native_token::report_native_token_usage(&job.source_code, &native_token, &mut issues);
+
+    let address_context = AddressCheckContext {
+        registries,
+        target_chain: target_chain.as_ref(),
+        target_network: job.target_network.as_deref(),
+        precompile_statuses: has_precompile_context.then_some(&precompile_statuses),
+        deployment_timestamp,
+    };
+    address_checks::report_address_literals(&job.source_code, &address_context, &mut issues);
+
+    if let Some(input) = &job.bytecode {
+        match input.runtime_code() {
+            Ok(runtime) => {
+                let bytecode_context = BytecodeContext {
+                    registries,
+                    precompile_statuses: has_precompile_context.then_some(&precompile_statuses),
+                    target_evm: target_evm.as_ref(),
+                    native_token: &native_token,
+                    genesis: job.subnet_genesis.as_ref(),
+                    upgrade_config: job.upgrade_config.as_ref(),
+                    deployment_timestamp,
+                };
+                let mut bytecode_issues = Vec::new();
+                bytecode::report_bytecode(&runtime, &job.source_code, &bytecode_context, &mut bytecode_issues);
+                // A source-mapped finding the source checks already made on that line adds nothing.
+                bytecode_issues.retain(|b| !issues.iter().any(|i| i.line == b.line && i.issue_type == b.issue_type));
+                issues.extend(bytecode_issues);
             }
+            Err(e) => eprintln!("Skipping bytecode analysis for Job ID {}: {}", job.job_id, e),
         }
     }
-    
+
+    if let Some((contracts, compiled)) = contracts {
+        let limits = CodeSizeLimits::from_job(job.subnet_genesis.as_ref(), target_evm.as_ref());
+        code_size::report_code_size(&job.source_code, contracts, *compiled, &limits, &mut issues);
+    }
+
     if let Some(limit) = subnet_gas_limit {
         let simulated_function_cost = 1_000_000;
         if simulated_function_cost > limit {
             issues.push(PortabilityIssue {
                 line: 0,
                 issue_type: "Gas Limit Violation Prediction".to_string(),
+                severity: Severity::Critical,
                 description: format!("A function in this contract has an estimated cost of {} gas, which exceeds the target Subnet's blockGasLimit of {}.", simulated_function_cost, limit),
                 recommendation: "Optimize expensive functions or deploy to a Subnet with a higher block gas limit.".to_string(),
             });
@@ -152,14 +289,8 @@ fn analyze_portability_v3(job: &AnalysisJob) -> AnalysisResult {
 
     // --- V3 FIX: Use HashSet for robust deduplication ---
     let unique_issues: HashSet<PortabilityIssue> = issues.into_iter().collect();
-    let output: Vec<PortabilityIssue> = unique_issues.into_iter().collect();
+    unique_issues.into_iter().collect()
     // --- END OF FIX ---
-
-    AnalysisResult {
-        job_id: job.job_id.clone(),
-        worker_name: "SubnetPortabilityWorkerV3".to_string(),
-        output,
-    }
 }
 
 fn publish_result(con: &mut Connection, result: AnalysisResult) {
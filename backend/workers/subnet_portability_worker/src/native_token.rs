@@ -0,0 +1,205 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use sentinel_common::solidity::mask_comments_and_strings;
+
+use crate::genesis::{self, Genesis, PrecompileStatus, UpgradeConfig};
+use crate::{PortabilityIssue, Severity};
+
+/// What the job declares about the target chain's native token.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NativeTokenProfile {
+    pub symbol: Option<String>,
+    // The token is only used to pay for gas and carries no market value.
+    #[serde(default)]
+    pub valueless: bool,
+}
+
+/// Native token semantics of the target chain, from the genesis and the declared profile.
+pub struct NativeTokenSemantics {
+    // Whether a genesis or declared profile was provided at all.
+    has_context: bool,
+    symbol: String,
+    valueless: bool,
+    minter_enabled: bool,
+    // `None` when there is no genesis, or it lists no accounts, so the supply is unknown.
+    has_initial_supply: Option<bool>,
+    zero_base_fee: bool,
+}
+
+impl NativeTokenSemantics {
+    pub fn from_job(genesis: Option<&Genesis>, upgrade_config: Option<&UpgradeConfig>, profile: Option<&NativeTokenProfile>, deployment_timestamp: u64) -> Self {
+        let minter_events = genesis::precompile_events(genesis, upgrade_config, "contractNativeMinterConfig");
+        let minter_enabled = matches!(genesis::precompile_status(&minter_events, deployment_timestamp), PrecompileStatus::Active { .. });
+        let has_initial_supply = genesis.filter(|g| !g.alloc.is_empty()).map(|g| g.alloc.values().any(|a| a.balance.as_deref().is_some_and(is_nonzero_amount)));
+        NativeTokenSemantics {
+            has_context: genesis.is_some() || profile.is_some(),
+            symbol: profile.and_then(|p| p.symbol.clone()).unwrap_or_else(|| "the native token".to_string()),
+            valueless: profile.is_some_and(|p| p.valueless),
+            minter_enabled,
+            has_initial_supply,
+            zero_base_fee: genesis.and_then(|g| g.config.fee_config.min_base_fee) == Some(0),
+        }
+    }
+
+    pub fn has_context(&self) -> bool {
+        self.has_context
+    }
+
+    /// The genesis lists accounts but funds none of them and nobody can mint the token, so payable
+    /// flows can never be funded.
+    pub fn unfundable(&self) -> bool {
+        self.has_initial_supply == Some(false) && !self.minter_enabled
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NativeUse {
+    // `require(msg.value == PRICE)` and other comparisons against a non-zero amount.
+    PriceCheck,
+    // Arithmetic or assignment feeding contract accounting.
+    Accounting,
+    // Forwarded as call value, emitted, returned, or a plain non-zero check.
+    BenignRead,
+}
+
+struct UsePatterns {
+    forwarded: Regex,
+    comparison_after: Regex,
+    comparison_before: Regex,
+    arithmetic_after: Regex,
+    arithmetic_or_assignment_before: Regex,
+}
+
+pub fn report_native_token_usage(source: &str, semantics: &NativeTokenSemantics, issues: &mut Vec<PortabilityIssue>) {
+    let value_regex = Regex::new(r"\bmsg\.value\b|(\baddress\s*\(\s*this\s*\)|\b[A-Za-z_][\w\.\[\]]*)\.balance\b").unwrap();
+    let patterns = UsePatterns {
+        forwarded: Regex::new(r"(value\s*:|\.(transfer|send)\s*\()$").unwrap(),
+        comparison_after: Regex::new(r"^(==|!=|>=|<=|>|<)\s*([\w\.]+)").unwrap(),
+        comparison_before: Regex::new(r"([\w\.]+)\s*(==|!=|>=|<=|>|<)$").unwrap(),
+        arithmetic_after: Regex::new(r"^[-+*/%]").unwrap(),
+        arithmetic_or_assignment_before: Regex::new(r"([-+*/%]|[^=!<>]=)$").unwrap(),
+    };
+    let masked = mask_comments_and_strings(source);
+
+    for (i, line) in masked.lines().enumerate() {
+        let line_num = (i + 1) as u32;
+        let mut line_uses: Vec<(&str, NativeUse)> = Vec::new();
+        for m in value_regex.find_iter(line) {
+            let expr = if m.as_str() == "msg.value" { "msg.value" } else if m.as_str().starts_with("address") && m.as_str().contains("this") { "address(this).balance" } else { ".balance" };
+            let usage = classify_use(line, m.start(), m.end(), &patterns);
+            if !line_uses.contains(&(expr, usage)) {
+                line_uses.push((expr, usage));
+            }
+        }
+
+        for (expr, usage) in line_uses {
+            if usage == NativeUse::BenignRead && semantics.has_context && !semantics.unfundable() {
+                continue;
+            }
+            let (severity, reasons) = assess(expr, usage, semantics);
+            let what = match usage {
+                NativeUse::PriceCheck => format!("`{}` is compared against a fixed amount, so the contract prices something in {}.", expr, semantics.symbol),
+                NativeUse::Accounting => format!("`{}` feeds the contract's accounting.", expr),
+                NativeUse::BenignRead => format!("`{}` is read without driving pricing or accounting logic.", expr),
+            };
+            issues.push(PortabilityIssue {
+                line: line_num,
+                issue_type: "Native Token Assumption".to_string(),
+                severity,
+                description: format!("{} {}", what, reasons.join(" ")).trim_end().to_string(),
+                recommendation: match usage {
+                    NativeUse::PriceCheck => "Make prices configurable per deployment, or denominate them in an ERC20 token whose value you control on every chain.".to_string(),
+                    NativeUse::Accounting if expr == "address(this).balance" => "Track deposits in a storage variable instead of reading `address(this).balance`, which changes with forced transfers and minting.".to_string(),
+                    NativeUse::Accounting => "Confirm the accounting still holds when the native token has a different value, supply or minting policy than AVAX.".to_string(),
+                    NativeUse::BenignRead => "Be aware that the native token on a custom Subnet may not be AVAX and could have a different value, or no value at all.".to_string(),
+                },
+            });
+        }
+    }
+}
+
+pub fn assess(expr: &str, usage: NativeUse, semantics: &NativeTokenSemantics) -> (Severity, Vec<String>) {
+    let mut severity = match usage {
+        NativeUse::PriceCheck | NativeUse::Accounting => Severity::Medium,
+        NativeUse::BenignRead => Severity::Low,
+    };
+    let mut reasons = Vec::new();
+
+    if semantics.unfundable() {
+        severity = severity.max(Severity::Critical);
+        reasons.push("The genesis allocates no native balance and the native minter is disabled, so no account can ever send value: payable flows are unusable.".to_string());
+    }
+    if semantics.valueless && usage == NativeUse::PriceCheck {
+        severity = severity.max(Severity::High);
+        reasons.push(format!("{} is declared valueless, so the price can be paid at no real cost.", semantics.symbol));
+    }
+    if semantics.minter_enabled && usage != NativeUse::BenignRead {
+        let risk = if expr == "address(this).balance" { Severity::High } else { Severity::Medium };
+        severity = severity.max(risk);
+        reasons.push("The native minter precompile is enabled, so allow-listed minters can create native tokens at will (including directly into this contract).".to_string());
+    }
+    if semantics.zero_base_fee && usage == NativeUse::PriceCheck {
+        reasons.push("The target has a zero minimum base fee, so the native token may not even be needed for gas.".to_string());
+    }
+    if !semantics.has_context {
+        reasons.push("No genesis or native-token profile was provided; the target's native token may not be AVAX.".to_string());
+    }
+    (severity, reasons)
+}
+
+fn classify_use(line: &str, start: usize, end: usize, patterns: &UsePatterns) -> NativeUse {
+    let before = line[..start].trim_end();
+    let after = line[end..].trim_start();
+    let trimmed = line.trim_start();
+
+    if trimmed.starts_with("emit ") || trimmed.starts_with("return ") || patterns.forwarded.is_match(before) {
+        return NativeUse::BenignRead;
+    }
+
+    let operand = patterns.comparison_after.captures(after).map(|c| c[2].to_string())
+        .or_else(|| patterns.comparison_before.captures(before).map(|c| c[1].to_string()));
+    if let Some(operand) = operand {
+        return if operand == "0" { NativeUse::BenignRead } else { NativeUse::PriceCheck };
+    }
+
+    if patterns.arithmetic_after.is_match(after) || patterns.arithmetic_or_assignment_before.is_match(before) {
+        return NativeUse::Accounting;
+    }
+    NativeUse::BenignRead
+}
+
+fn is_nonzero_amount(amount: &str) -> bool {
+    let amount = amount.trim();
+    match amount.strip_prefix("0x").or_else(|| amount.strip_prefix("0X")) {
+        Some(hex) => hex.chars().any(|c| c != '0'),
+        None => amount.chars().any(|c| c.is_ascii_digit() && c != '0'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn semantics(genesis: serde_json::Value) -> NativeTokenSemantics {
+        let genesis: Genesis = serde_json::from_value(genesis).unwrap();
+        NativeTokenSemantics::from_job(Some(&genesis), None, None, 0)
+    }
+
+    #[test]
+    fn genesis_without_alloc_has_unknown_supply() {
+        assert!(!semantics(json!({ "config": { "feeConfig": {} } })).unfundable());
+        assert!(!semantics(json!({ "config": { "feeConfig": {} }, "alloc": {} })).unfundable());
+    }
+
+    #[test]
+    fn listed_accounts_without_balance_or_minter_are_unfundable() {
+        let alloc = json!({ "0x8db97C7cEcE249c2b98bDC0226Cc4C2A57BF52FC": { "balance": "0x0" } });
+        assert!(semantics(json!({ "config": { "feeConfig": {} }, "alloc": alloc })).unfundable());
+        assert!(!semantics(json!({ "config": { "feeConfig": {}, "contractNativeMinterConfig": {} }, "alloc": alloc })).unfundable());
+        let funded = json!({ "0x8db97C7cEcE249c2b98bDC0226Cc4C2A57BF52FC": { "balance": "0x52B7D2DCC80CD2E4000000" } });
+        assert!(!semantics(json!({ "config": { "feeConfig": {} }, "alloc": funded })).unfundable());
+    }
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::genesis::Genesis;
+
+// --- Target network profile: what is actually deployed on the chain the contract will move to ---
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetProfile {
+    pub name: Option<String>,
+    // Known-deployment manifest: address -> deployed code (or its hash).
+    #[serde(default)]
+    pub deployments: HashMap<String, TargetAccount>,
+    // Local chain state snapshot, e.g. the output of `geth dump`.
+    pub state_snapshot: Option<StateSnapshot>,
+    // Gas schedule overrides for Subnets that reprice opcodes, keyed like `registry/gas_schedule.json`.
+    pub gas_schedule: Option<Map<String, Value>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetAccount {
+    pub code: Option<String>,
+    pub code_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct StateSnapshot {
+    #[serde(default)]
+    pub accounts: HashMap<String, TargetAccount>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TargetCode {
+    Code { code_hash: Option<String> },
+    Empty,
+}
+
+/// Merged view of the genesis `alloc`, the deployment manifest and the state snapshot.
+pub struct TargetChain {
+    pub name: String,
+    // Address -> (account, listed in the deployment manifest).
+    accounts: HashMap<String, (TargetAccount, bool)>,
+}
+
+impl TargetChain {
+    /// Returns `None` when the job carries no information about deployed code on the target.
+    pub fn from_job(genesis: Option<&Genesis>, profile: Option<&TargetProfile>) -> Option<Self> {
+        let alloc = genesis.map(|g| &g.alloc).filter(|a| !a.is_empty());
+        if alloc.is_none() && profile.is_none() {
+            return None;
+        }
+
+        let mut accounts = HashMap::new();
+        for (address, account) in alloc.into_iter().flatten() {
+            let account = TargetAccount { code: account.code.clone(), code_hash: None };
+            accounts.insert(normalize_address(address), (account, false));
+        }
+        if let Some(profile) = profile {
+            for (address, account) in profile.state_snapshot.iter().flat_map(|s| s.accounts.iter()) {
+                accounts.insert(normalize_address(address), (account.clone(), false));
+            }
+            for (address, account) in &profile.deployments {
+                accounts.insert(normalize_address(address), (account.clone(), true));
+            }
+        }
+
+        let name = profile.and_then(|p| p.name.clone()).unwrap_or_else(|| "the target Subnet".to_string());
+        Some(TargetChain { name, accounts })
+    }
+
+    pub fn code_at(&self, address: &str) -> TargetCode {
+        let Some((account, from_manifest)) = self.accounts.get(&normalize_address(address)) else {
+            return TargetCode::Empty;
+        };
+        let code_hash = match (&account.code, &account.code_hash) {
+            (Some(code), _) => sentinel_common::decode_hex(code)
+                .filter(|code| !code.is_empty())
+                .map(|code| sentinel_common::encode_hex(&sentinel_common::keccak256(&code))),
+            (None, Some(hash)) => Some(hash.to_lowercase()).filter(|h| h != EMPTY_CODE_HASH),
+            (None, None) => None,
+        };
+        match code_hash {
+            Some(hash) => TargetCode::Code { code_hash: Some(hash) },
+            // A manifest entry without code still records that something is deployed there.
+            None if *from_manifest && account.code.is_none() && account.code_hash.is_none() => TargetCode::Code { code_hash: None },
+            None => TargetCode::Empty,
+        }
+    }
+}
+
+// keccak256 of empty bytecode, reported by state dumps for accounts without code.
+const EMPTY_CODE_HASH: &str = "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
+
+fn normalize_address(address: &str) -> String {
+    let address = address.trim().to_lowercase();
+    if address.starts_with("0x") { address } else { format!("0x{}", address) }
+}
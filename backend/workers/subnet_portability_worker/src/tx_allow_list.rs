@@ -0,0 +1,95 @@
+use regex::Regex;
+
+use sentinel_common::solidity::{line_at, mask_comments_and_strings, FunctionKind, FunctionSpan, SourceOutline};
+
+use crate::genesis::{self, Genesis, PrecompileStatus, UpgradeConfig};
+use crate::{PortabilityIssue, Severity};
+
+// Name prefixes of entry points meant to be called by arbitrary users.
+const USER_FACING_PREFIXES: &[&str] = &[
+    "mint", "swap", "deposit", "withdraw", "buy", "sell", "stake", "unstake", "claim", "redeem", "bid",
+    "vote", "register", "join", "enter", "borrow", "repay", "supply", "lend", "liquidate", "addliquidity",
+    "removeliquidity", "transfer", "approve", "bridge", "purchase", "play",
+];
+
+/// When the target Subnet enables the Transaction Allow List, only allow-listed EOAs can submit
+/// transactions. Reports the permissionless entry points that non-listed users can no longer
+/// reach, and the meta-transaction paths where only the relayer has to be listed.
+pub fn report_tx_allow_list_impact(source: &str, genesis: Option<&Genesis>, upgrade_config: Option<&UpgradeConfig>, deployment_timestamp: u64, issues: &mut Vec<PortabilityIssue>) {
+    let events = genesis::precompile_events(genesis, upgrade_config, "txAllowListConfig");
+    let enforced_from = match genesis::precompile_status(&events, deployment_timestamp) {
+        PrecompileStatus::Active { .. } => None,
+        PrecompileStatus::NotYetActive { activates_at } => Some(activates_at),
+        PrecompileStatus::NotConfigured | PrecompileStatus::Disabled { .. } => return,
+    };
+    let restriction = match enforced_from {
+        None => "The target Subnet enables the Transaction Allow List".to_string(),
+        Some(ts) => format!("The target Subnet enables the Transaction Allow List at timestamp {}", ts),
+    };
+    let listed = genesis::allow_list_addresses(genesis, "txAllowListConfig");
+    let listed_note = if listed.is_empty() { String::new() } else { format!(" The genesis currently allow-lists {}.", listed.join(", ")) };
+
+    let sender_check = Regex::new(r"msg\.sender\s*==|==\s*msg\.sender|_checkOwner\s*\(|_checkRole\s*\(|hasRole\s*\(").unwrap();
+    let masked = mask_comments_and_strings(source);
+    let outline = SourceOutline::parse(source);
+    for function in outline.functions.iter().filter(|f| is_permissionless_entry_point(f, &masked[f.body.clone()], &sender_check)) {
+        let entry = match function.kind {
+            FunctionKind::Receive | FunctionKind::Fallback => format!("The `{}` function accepts calls from arbitrary senders.", function.name),
+            _ => format!("`{}` is a permissionless entry point meant for arbitrary users.", function.name),
+        };
+        issues.push(PortabilityIssue {
+            line: function.start_line,
+            issue_type: "Transaction Allow List Restriction".to_string(),
+            severity: if enforced_from.is_none() { Severity::High } else { Severity::Medium },
+            description: format!("{} {}, so only allow-listed EOAs can send the transactions that reach it.{}", entry, restriction, listed_note),
+            recommendation: "Plan how end users get onto the allow list (e.g. an onboarding flow that calls `setEnabled` on 0x0200000000000000000000000000000000000002), or serve them through an allow-listed relayer.".to_string(),
+        });
+    }
+
+    for (line, pattern) in meta_transaction_sites(&masked) {
+        issues.push(PortabilityIssue {
+            line,
+            issue_type: "Relayer Allow List Dependency".to_string(),
+            severity: Severity::Medium,
+            description: format!("The contract accepts {}. {}, which checks the account that submits the transaction: the relayer or forwarder must be allow-listed, while the signing users need not be.{}", pattern, restriction, listed_note),
+            recommendation: "Allow-list the relayer accounts before launch, and decide whether signature-based entry points should let non-listed users act through them.".to_string(),
+        });
+    }
+}
+
+fn is_permissionless_entry_point(function: &FunctionSpan, body: &str, sender_check: &Regex) -> bool {
+    let header = &function.header;
+    let words: Vec<&str> = header.split(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '$').filter(|w| !w.is_empty()).collect();
+    let state_changing = !words.iter().any(|w| matches!(*w, "view" | "pure"));
+    let restricted = words.iter().skip(1).any(|w| w.starts_with("only") || matches!(*w, "auth" | "requiresAuth" | "restricted" | "initializer" | "reinitializer"))
+        || sender_check.is_match(body);
+    if !state_changing || restricted {
+        return false;
+    }
+    match function.kind {
+        FunctionKind::Receive | FunctionKind::Fallback => true,
+        FunctionKind::Function => {
+            let external = words.iter().any(|w| matches!(*w, "public" | "external"));
+            let name = function.name.to_lowercase();
+            external && (words.contains(&"payable") || USER_FACING_PREFIXES.iter().any(|p| name.starts_with(p)))
+        }
+        FunctionKind::Constructor | FunctionKind::Modifier => false,
+    }
+}
+
+/// First occurrence of each meta-transaction pattern, as (line, description).
+fn meta_transaction_sites(masked: &str) -> Vec<(u32, &'static str)> {
+    let patterns = [
+        (r"\bERC2771Context\w*\b|\bisTrustedForwarder\s*\(", "ERC-2771 meta-transactions through a trusted forwarder"),
+        (r"\bexecuteMetaTransaction\s*\(", "native meta-transactions (`executeMetaTransaction`)"),
+        (r"\bfunction\s+permit\s*\(", "EIP-2612 `permit` signatures that anyone can submit"),
+        (r"\becrecover\s*\(|\bECDSA\s*\.\s*(try)?[Rr]ecover\s*\(|\bSignatureChecker\s*\.", "signed messages that a third party can submit on the signer's behalf"),
+    ];
+    let mut sites: Vec<(u32, &'static str)> = Vec::new();
+    for (pattern, description) in patterns {
+        if let Some(m) = Regex::new(pattern).unwrap().find(masked) {
+            sites.push((line_at(masked, m.start()), description));
+        }
+    }
+    sites
+}